@@ -0,0 +1,496 @@
+//! Compact, versioned binary encoding for [`Tree`].
+//!
+//! Layout (all integers little-endian):
+//!
+//! | Field    | Size                 | Description                                       |
+//! | -------- | -------------------- | ------------------------------------------------- |
+//! | magic    | 2 bytes              | `b"BT"`                                           |
+//! | version  | 1 byte               | [`FORMAT_VERSION`]                                |
+//! | count    | varint               | number of nodes `n`                               |
+//! | shape    | `ceil(2n / 8)` bytes | 2 bits per node in preorder: has-left, has-right  |
+//! | keys     | `n` varints          | node keys in preorder                             |
+//! | checksum | 4 bytes              | FNV-1a of every preceding byte                    |
+//!
+//! Node ids are renumbered in preorder on decoding, so the root keeps id `0`
+//! but other ids may differ from the ones returned by [`Tree::add_node`].
+//!
+//! With the `serde` feature, deserialized trees keep their ids but go through
+//! the same shape check: every node must be reachable from the root through
+//! exactly one parent.
+
+use std::fmt;
+
+use crate::{Node, Tree};
+
+const MAGIC: &[u8; 2] = b"BT";
+
+/// Current version of the binary format written by [`Tree::to_bytes`].
+pub const FORMAT_VERSION: u8 = 1;
+
+const CHECKSUM_LEN: usize = 4;
+
+/// Error returned by [`Tree::from_bytes`] when the input is not a valid encoding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The input ended before the encoding was complete.
+    UnexpectedEof,
+    /// The input does not start with the expected magic bytes.
+    BadMagic,
+    /// The encoding was produced by an unsupported format version.
+    UnsupportedVersion(u8),
+    /// The stored checksum does not match the content.
+    ChecksumMismatch,
+    /// A varint is longer than allowed or overflows its target type.
+    InvalidVarint,
+    /// The encoding declares a tree without nodes.
+    EmptyTree,
+    /// The shape bits do not describe a single binary tree with `count` nodes.
+    InvalidShape,
+    /// Extra bytes follow the last key.
+    TrailingBytes,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEof => write!(f, "unexpected end of input"),
+            Self::BadMagic => write!(f, "missing tree magic bytes"),
+            Self::UnsupportedVersion(v) => write!(f, "unsupported format version {v}"),
+            Self::ChecksumMismatch => write!(f, "checksum mismatch"),
+            Self::InvalidVarint => write!(f, "invalid varint"),
+            Self::EmptyTree => write!(f, "tree must contain at least the root"),
+            Self::InvalidShape => write!(f, "shape bits do not describe a valid tree"),
+            Self::TrailingBytes => write!(f, "trailing bytes after the last key"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl Tree {
+    /// Encodes the tree in the compact binary format described in [`crate::codec`].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let n = self.nodes.len();
+        let mut shape = vec![0_u8; (2 * n).div_ceil(8)];
+        let mut keys = Vec::with_capacity(n);
+
+        // Iterative preorder: deep trees must not overflow the call stack.
+        let mut stack = vec![0_usize];
+        let mut visited = 0;
+        while let Some(id) = stack.pop() {
+            let node = &self.nodes[id];
+            let bit = 2 * visited;
+            if node.id_left.is_some() {
+                shape[bit / 8] |= 1 << (bit % 8);
+            }
+            if node.id_right.is_some() {
+                shape[(bit + 1) / 8] |= 1 << ((bit + 1) % 8);
+            }
+            write_varint(&mut keys, u64::from(node.key));
+            visited += 1;
+
+            // Push right first so that the left subtree is visited first.
+            stack.extend(node.id_right);
+            stack.extend(node.id_left);
+        }
+
+        let mut bytes = Vec::with_capacity(MAGIC.len() + 1 + 10 + shape.len() + keys.len() + 4);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(FORMAT_VERSION);
+        write_varint(&mut bytes, visited as u64);
+        bytes.extend_from_slice(&shape);
+        bytes.extend_from_slice(&keys);
+        let checksum = fnv1a(&bytes);
+        bytes.extend_from_slice(&checksum.to_le_bytes());
+        bytes
+    }
+
+    /// Decodes a tree previously encoded with [`Tree::to_bytes`].
+    ///
+    /// Returns a [`DecodeError`] if the input is truncated, corrupted, or was
+    /// written by an unsupported format version.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let body = bytes
+            .strip_prefix(MAGIC)
+            .ok_or(if bytes.len() < MAGIC.len() {
+                DecodeError::UnexpectedEof
+            } else {
+                DecodeError::BadMagic
+            })?;
+
+        let (&version, _) = body.split_first().ok_or(DecodeError::UnexpectedEof)?;
+        if version != FORMAT_VERSION {
+            return Err(DecodeError::UnsupportedVersion(version));
+        }
+
+        if body.len() < 1 + CHECKSUM_LEN {
+            return Err(DecodeError::UnexpectedEof);
+        }
+        let (content, stored) = bytes.split_at(bytes.len() - CHECKSUM_LEN);
+        let stored = u32::from_le_bytes(stored.try_into().unwrap());
+        if fnv1a(content) != stored {
+            return Err(DecodeError::ChecksumMismatch);
+        }
+
+        let mut reader = Reader {
+            bytes: &content[MAGIC.len() + 1..],
+        };
+        let n = usize::try_from(reader.varint()?).map_err(|_| DecodeError::InvalidVarint)?;
+        if n == 0 {
+            return Err(DecodeError::EmptyTree);
+        }
+
+        // Every key takes at least one byte: reject impossible counts before allocating.
+        let shape_len = n
+            .checked_mul(2)
+            .ok_or(DecodeError::InvalidShape)?
+            .div_ceil(8);
+        if reader.bytes.len() < shape_len.saturating_add(n) {
+            return Err(DecodeError::UnexpectedEof);
+        }
+        let shape = reader.take(shape_len)?;
+
+        let mut nodes: Vec<Node> = Vec::with_capacity(n);
+        // Pending child slots as (parent_id, is_left); the top is filled next.
+        let mut slots: Vec<(usize, bool)> = Vec::new();
+
+        for id in 0..n {
+            let key = u32::try_from(reader.varint()?).map_err(|_| DecodeError::InvalidVarint)?;
+            nodes.push(Node::new(key));
+
+            if id > 0 {
+                let (parent, is_left) = slots.pop().ok_or(DecodeError::InvalidShape)?;
+                if is_left {
+                    nodes[parent].id_left = Some(id);
+                } else {
+                    nodes[parent].id_right = Some(id);
+                }
+            }
+
+            let bit = 2 * id;
+            if shape[(bit + 1) / 8] >> ((bit + 1) % 8) & 1 == 1 {
+                slots.push((id, false));
+            }
+            if shape[bit / 8] >> (bit % 8) & 1 == 1 {
+                slots.push((id, true));
+            }
+        }
+
+        if !slots.is_empty() {
+            return Err(DecodeError::InvalidShape);
+        }
+        // Padding bits in the last shape byte must be zero.
+        if (2 * n) % 8 != 0 && shape[shape_len - 1] >> ((2 * n) % 8) != 0 {
+            return Err(DecodeError::InvalidShape);
+        }
+        if !reader.bytes.is_empty() {
+            return Err(DecodeError::TrailingBytes);
+        }
+
+        debug_assert_eq!(check_shape(&nodes), Ok(()));
+        Ok(Self { nodes })
+    }
+}
+
+/// Unchecked node list, deserialized before being validated into a [`Tree`].
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+pub(crate) struct RawTree {
+    nodes: Vec<Node>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RawTree> for Tree {
+    type Error = DecodeError;
+
+    fn try_from(raw: RawTree) -> Result<Self, DecodeError> {
+        check_shape(&raw.nodes)?;
+        Ok(Self { nodes: raw.nodes })
+    }
+}
+
+/// Checks that `nodes` is a single binary tree rooted at id `0`: every child
+/// id is in range and every node is reached exactly once from the root.
+///
+/// Rejecting repeated visits also rejects cycles, which would otherwise make
+/// the recursive queries and [`Tree::to_bytes`] loop forever.
+fn check_shape(nodes: &[Node]) -> Result<(), DecodeError> {
+    if nodes.is_empty() {
+        return Err(DecodeError::EmptyTree);
+    }
+
+    let mut seen = vec![false; nodes.len()];
+    let mut stack = vec![0_usize];
+    let mut visited = 0;
+    while let Some(id) = stack.pop() {
+        if id >= nodes.len() || std::mem::replace(&mut seen[id], true) {
+            return Err(DecodeError::InvalidShape);
+        }
+        visited += 1;
+        stack.extend(nodes[id].id_right);
+        stack.extend(nodes[id].id_left);
+    }
+
+    if visited != nodes.len() {
+        return Err(DecodeError::InvalidShape);
+    }
+    Ok(())
+}
+
+/// Cursor over the encoded body.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        if self.bytes.len() < len {
+            return Err(DecodeError::UnexpectedEof);
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    /// Reads an unsigned LEB128 varint of at most 10 bytes.
+    fn varint(&mut self) -> Result<u64, DecodeError> {
+        let mut value = 0_u64;
+        for i in 0..10 {
+            let byte = self.take(1)?[0];
+            let payload = u64::from(byte & 0x7f);
+            if i == 9 && payload > 1 {
+                return Err(DecodeError::InvalidVarint);
+            }
+            value |= payload << (7 * i);
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(DecodeError::InvalidVarint)
+    }
+}
+
+/// Appends `value` as an unsigned LEB128 varint.
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// 32-bit FNV-1a hash, used as a lightweight corruption check.
+fn fnv1a(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c_9dc5, |hash, &b| {
+        (hash ^ u32::from(b)).wrapping_mul(0x0100_0193)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds the tree
+    /// ```text
+    ///         1
+    ///        / \
+    ///       50  2
+    ///      / \   \
+    ///     30 40   300000
+    /// ```
+    fn sample_tree() -> Tree {
+        let mut tree = Tree::with_root(1);
+        tree.add_node(0, 50, true);
+        tree.add_node(0, 2, false);
+        tree.add_node(2, 300_000, false);
+        tree.add_node(1, 30, true);
+        tree.add_node(1, 40, false);
+        tree
+    }
+
+    /// Returns `(key, has_left, has_right)` for every node in preorder.
+    fn preorder(tree: &Tree) -> Vec<(u32, bool, bool)> {
+        let mut out = Vec::new();
+        let mut stack = vec![0];
+        while let Some(id) = stack.pop() {
+            let node = &tree.nodes[id];
+            out.push((node.key, node.id_left.is_some(), node.id_right.is_some()));
+            stack.extend(node.id_right);
+            stack.extend(node.id_left);
+        }
+        out
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let tree = sample_tree();
+        let decoded = Tree::from_bytes(&tree.to_bytes()).unwrap();
+
+        assert_eq!(preorder(&decoded), preorder(&tree));
+        assert_eq!(decoded.sum(), tree.sum());
+        assert_eq!(decoded.max_path_sum(), tree.max_path_sum());
+    }
+
+    #[test]
+    fn test_round_trip_single_node_and_extreme_keys() {
+        let tree = Tree::with_root(u32::MAX);
+        let decoded = Tree::from_bytes(&tree.to_bytes()).unwrap();
+        assert_eq!(preorder(&decoded), vec![(u32::MAX, false, false)]);
+
+        let mut tree = Tree::with_root(0);
+        tree.add_node(0, u32::MAX, false);
+        let decoded = Tree::from_bytes(&tree.to_bytes()).unwrap();
+        assert_eq!(preorder(&decoded), preorder(&tree));
+    }
+
+    #[test]
+    fn test_round_trip_deep_chain() {
+        // Deep enough to overflow a recursive encoder on small stacks.
+        let mut tree = Tree::with_root(0);
+        let mut last = 0;
+        for key in 1..200_000 {
+            last = tree.add_node(last, key % 1000, key % 2 == 0);
+        }
+        let decoded = Tree::from_bytes(&tree.to_bytes()).unwrap();
+        assert_eq!(preorder(&decoded), preorder(&tree));
+    }
+
+    #[test]
+    fn test_detects_truncation() {
+        let bytes = sample_tree().to_bytes();
+        for len in 0..bytes.len() {
+            assert!(
+                Tree::from_bytes(&bytes[..len]).is_err(),
+                "truncated input of length {len} was accepted"
+            );
+        }
+    }
+
+    #[test]
+    fn test_detects_bit_flips() {
+        let bytes = sample_tree().to_bytes();
+        for i in 0..bytes.len() {
+            for bit in 0..8 {
+                let mut corrupted = bytes.clone();
+                corrupted[i] ^= 1 << bit;
+                assert!(
+                    Tree::from_bytes(&corrupted).is_err(),
+                    "flipping bit {bit} of byte {i} was not detected"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_rejects_bad_header() {
+        let mut bytes = sample_tree().to_bytes();
+        bytes[0] = b'X';
+        assert_eq!(Tree::from_bytes(&bytes).err(), Some(DecodeError::BadMagic));
+
+        let mut bytes = sample_tree().to_bytes();
+        bytes[2] = FORMAT_VERSION + 1;
+        assert_eq!(
+            Tree::from_bytes(&bytes).err(),
+            Some(DecodeError::UnsupportedVersion(FORMAT_VERSION + 1))
+        );
+    }
+
+    /// Re-seals a hand-crafted body with a valid checksum.
+    fn seal(mut content: Vec<u8>) -> Vec<u8> {
+        let checksum = fnv1a(&content);
+        content.extend_from_slice(&checksum.to_le_bytes());
+        content
+    }
+
+    #[test]
+    fn test_rejects_inconsistent_shape() {
+        // Root claims a left child, but only one node is declared.
+        let bytes = seal(vec![b'B', b'T', FORMAT_VERSION, 1, 0b01, 7]);
+        assert_eq!(
+            Tree::from_bytes(&bytes).err(),
+            Some(DecodeError::InvalidShape)
+        );
+
+        // Two nodes declared, but the root has no children.
+        let bytes = seal(vec![b'B', b'T', FORMAT_VERSION, 2, 0b0000, 7, 8]);
+        assert_eq!(
+            Tree::from_bytes(&bytes).err(),
+            Some(DecodeError::InvalidShape)
+        );
+
+        // No nodes at all.
+        let bytes = seal(vec![b'B', b'T', FORMAT_VERSION, 0]);
+        assert_eq!(Tree::from_bytes(&bytes).err(), Some(DecodeError::EmptyTree));
+    }
+
+    #[test]
+    fn test_rejects_trailing_bytes_and_oversized_keys() {
+        let bytes = seal(vec![b'B', b'T', FORMAT_VERSION, 1, 0, 7, 0]);
+        assert_eq!(
+            Tree::from_bytes(&bytes).err(),
+            Some(DecodeError::TrailingBytes)
+        );
+
+        // 2^32 does not fit in a u32 key.
+        let bytes = seal(vec![
+            b'B',
+            b'T',
+            FORMAT_VERSION,
+            1,
+            0,
+            0x80,
+            0x80,
+            0x80,
+            0x80,
+            0x10,
+        ]);
+        assert_eq!(
+            Tree::from_bytes(&bytes).err(),
+            Some(DecodeError::InvalidVarint)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let tree = sample_tree();
+        let json = serde_json::to_string(&tree).unwrap();
+        let decoded: Tree = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(preorder(&decoded), preorder(&tree));
+        assert_eq!(decoded.nodes.len(), tree.nodes.len());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_rejects_invalid_shapes() {
+        let node = |key: u32, left: &str, right: &str| {
+            format!(r#"{{"key":{key},"id_left":{left},"id_right":{right}}}"#)
+        };
+        let parse = |nodes: &[String]| {
+            serde_json::from_str::<Tree>(&format!(r#"{{"nodes":[{}]}}"#, nodes.join(",")))
+        };
+
+        // Cycle: 0 -> 1 -> 0
+        let Err(err) = parse(&[node(1, "1", "null"), node(2, "0", "null")]) else {
+            panic!("cyclic tree was accepted");
+        };
+        assert!(err.to_string().contains("shape"), "{err}");
+
+        // Child id out of range
+        assert!(parse(&[node(1, "null", "5")]).is_err());
+        // Node 1 has two parents
+        assert!(parse(&[node(1, "1", "1"), node(2, "null", "null")]).is_err());
+        // Node 2 is unreachable from the root
+        assert!(
+            parse(&[
+                node(1, "1", "null"),
+                node(2, "null", "null"),
+                node(3, "2", "null")
+            ])
+            .is_err()
+        );
+        // No nodes at all
+        assert!(parse(&[]).is_err());
+    }
+}
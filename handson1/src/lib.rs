@@ -1,3 +1,7 @@
+pub mod codec;
+pub mod parallel;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Node {
     key: u32,
     id_left: Option<usize>,
@@ -14,6 +18,8 @@ impl Node {
     }
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "codec::RawTree"))]
 pub struct Tree {
     nodes: Vec<Node>,
 }
@@ -102,16 +108,16 @@ impl Tree {
         let node = &self.nodes[id];
 
         // Check if current node's key violates BST property
-        if let Some(min_val) = min {
-            if node.key <= min_val {
-                return false;
-            }
+        if let Some(min_val) = min
+            && node.key <= min_val
+        {
+            return false;
         }
 
-        if let Some(max_val) = max {
-            if node.key >= max_val {
-                return false;
-            }
+        if let Some(max_val) = max
+            && node.key >= max_val
+        {
+            return false;
         }
 
         // Recursively check left and right subtrees
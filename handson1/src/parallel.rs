@@ -0,0 +1,177 @@
+//! Multi-threaded aggregates for very large trees.
+//!
+//! The tree is split into independent subtrees by expanding a frontier from
+//! the root in BFS order; the nodes above the frontier are aggregated on the
+//! calling thread, while the frontier subtrees are distributed across scoped
+//! threads and visited iteratively.
+
+use std::iter::Sum;
+use std::num::NonZeroUsize;
+use std::ops::Add;
+use std::thread;
+
+use crate::{Node, Tree};
+
+/// Number of frontier subtrees created per worker thread, to balance uneven subtrees.
+const SUBTREES_PER_THREAD: usize = 4;
+
+impl Tree {
+    /// Parallel version of [`Tree::sum`]: returns the sum of all the keys in the tree.
+    pub fn par_sum(&self) -> u32 {
+        self.par_aggregate(default_threads(), |node| node.key)
+    }
+
+    /// Returns the number of leaves in the tree, counting them in parallel.
+    pub fn par_count_leaves(&self) -> usize {
+        self.par_aggregate(default_threads(), |node| {
+            usize::from(node.id_left.is_none() && node.id_right.is_none())
+        })
+    }
+
+    /// Sums `value(node)` over every node reachable from the root, using up to
+    /// `threads` worker threads.
+    fn par_aggregate<T, F>(&self, threads: usize, value: F) -> T
+    where
+        T: Send + Sum + Add<Output = T>,
+        F: Fn(&Node) -> T + Sync,
+    {
+        let target = threads * SUBTREES_PER_THREAD;
+
+        // Expand the frontier until there are enough subtrees to share out.
+        let mut top = Vec::new();
+        let mut frontier = std::collections::VecDeque::from([0_usize]);
+        while frontier.len() < target {
+            let Some(id) = frontier.pop_front() else {
+                break;
+            };
+            let node = &self.nodes[id];
+            top.push(value(node));
+            frontier.extend(node.id_left);
+            frontier.extend(node.id_right);
+        }
+        let top: T = top.into_iter().sum();
+
+        if frontier.is_empty() {
+            return top;
+        }
+
+        let frontier = Vec::from(frontier);
+        let chunk = frontier.len().div_ceil(threads);
+        let value = &value;
+
+        let partials: Vec<T> = thread::scope(|scope| {
+            let handles: Vec<_> = frontier
+                .chunks(chunk)
+                .map(|roots| {
+                    scope.spawn(move || {
+                        roots
+                            .iter()
+                            .map(|&root| self.subtree_aggregate(root, value))
+                            .sum::<T>()
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("Worker thread panicked"))
+                .collect()
+        });
+
+        top + partials.into_iter().sum()
+    }
+
+    /// Sums `value(node)` over the subtree rooted at `root` with an explicit
+    /// stack, so that degenerate subtrees cannot overflow a worker's stack.
+    fn subtree_aggregate<T, F>(&self, root: usize, value: &F) -> T
+    where
+        T: Sum,
+        F: Fn(&Node) -> T,
+    {
+        let mut stack = vec![root];
+        std::iter::from_fn(|| {
+            let id = stack.pop()?;
+            assert!(id < self.nodes.len(), "Node id is out of range");
+            let node = &self.nodes[id];
+            stack.extend(node.id_left);
+            stack.extend(node.id_right);
+            Some(value(node))
+        })
+        .sum()
+    }
+}
+
+fn default_threads() -> usize {
+    thread::available_parallelism().map_or(1, NonZeroUsize::get)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Minimal xorshift generator, enough to build reproducible random trees.
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, bound: usize) -> usize {
+            (self.next() % bound as u64) as usize
+        }
+    }
+
+    /// Builds a random tree with `n` nodes by attaching each new node to a
+    /// random free child slot of an existing node.
+    fn random_tree(n: usize, seed: u64) -> Tree {
+        let mut rng = XorShift(seed);
+        let mut tree = Tree::with_root(rng.below(100) as u32);
+        let mut free = vec![(0, true), (0, false)];
+
+        for _ in 1..n {
+            let (parent, is_left) = free.swap_remove(rng.below(free.len()));
+            let id = tree.add_node(parent, rng.below(100) as u32, is_left);
+            free.push((id, true));
+            free.push((id, false));
+        }
+        tree
+    }
+
+    #[test]
+    fn test_par_matches_sequential_on_random_trees() {
+        for (seed, n) in [(1, 1), (2, 2), (3, 17), (4, 1_000), (5, 50_000)] {
+            let tree = random_tree(n, seed);
+            let sum = tree.sum();
+            let leaves = tree.count_leaves(Some(0));
+
+            assert_eq!(tree.par_sum(), sum, "sum mismatch for seed {seed}");
+            assert_eq!(
+                tree.par_count_leaves(),
+                leaves,
+                "leaf mismatch for seed {seed}"
+            );
+
+            for threads in [1, 2, 3, 8] {
+                assert_eq!(tree.par_aggregate(threads, |node| node.key), sum);
+            }
+        }
+    }
+
+    #[test]
+    fn test_par_on_degenerate_chain() {
+        // A chain has a single subtree at every frontier level.
+        let mut tree = Tree::with_root(1);
+        let mut last = 0;
+        for key in 0..100_000 {
+            last = tree.add_node(last, key % 7, key % 3 == 0);
+        }
+
+        let expected: u32 = 1 + (0..100_000).map(|key| key % 7).sum::<u32>();
+        assert_eq!(tree.par_sum(), expected);
+        assert_eq!(tree.par_count_leaves(), 1);
+    }
+}
@@ -0,0 +1,53 @@
+//! IsThere backends on an adversarial coverage pattern.
+//!
+//! Run with `cargo bench --bench problem2`.
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use handson2::problem2::{Backend, solve_with};
+
+use common::XorShift;
+
+const PAIRS: u64 = 20_000;
+const QUERIES: u64 = 20_000;
+
+/// Coverage alternating between 0 and 2, queried for `k = 1` over random
+/// ranges: min/max pruning never fires, while the answer is always `0`.
+fn alternating_input() -> String {
+    let mut rng = XorShift(0x9E37_79B9_7F4A_7C15);
+
+    let mut lines = vec![format!("{} {QUERIES}", 2 * PAIRS)];
+    for i in 0..PAIRS {
+        lines.push(format!("{0} {0}", 2 * i + 1));
+        lines.push(format!("{0} {0}", 2 * i + 1));
+    }
+    for _ in 0..QUERIES {
+        let a = rng.next() % (2 * PAIRS);
+        let b = rng.next() % (2 * PAIRS);
+        lines.push(format!("{} {} 1", a.min(b), a.max(b)));
+    }
+    lines.join("\n")
+}
+
+fn main() {
+    let input = alternating_input();
+    println!("{PAIRS} alternating pairs, {QUERIES} queries with k = 1 (release build)");
+
+    for backend in [
+        Backend::Dense,
+        Backend::Sparse,
+        Backend::Compressed,
+        Backend::PositionLists,
+        Backend::Mo,
+        Backend::SparseTable,
+        Backend::Iterative,
+    ] {
+        let start = Instant::now();
+        black_box(solve_with(black_box(&input), backend));
+        println!("{:<40} {:>12.2?}", format!("{backend:?}"), start.elapsed());
+    }
+}
@@ -0,0 +1,81 @@
+//! Throughput of the recursive and iterative lazy segment tree backends.
+//!
+//! Run with `cargo bench --bench segtree`.
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use handson2::iter_segtree::IterLazySegTree;
+use handson2::lazy_segtree::{Add, LazySegTree, MinMax, MinMaxValue, Sum, SumLen};
+
+use common::XorShift;
+
+const N: usize = 1_000_000;
+const OPS: usize = 1_000_000;
+
+/// Random half-open ranges plus a random value, shared by both backends.
+fn operations(seed: u64) -> Vec<(usize, usize, i64)> {
+    let mut rng = XorShift(seed);
+    (0..OPS)
+        .map(|_| {
+            let a = rng.below(N + 1);
+            let b = rng.below(N + 1);
+            (a.min(b), a.max(b), rng.below(21) as i64 - 10)
+        })
+        .collect()
+}
+
+fn report(name: &str, elapsed: Duration) {
+    let throughput = OPS as f64 / elapsed.as_secs_f64() / 1e6;
+    println!("{name:<40} {elapsed:>12.2?} {throughput:>8.2} Mops/s");
+}
+
+/// Alternates range additions and range sum queries.
+macro_rules! bench_sum_add {
+    ($tree:ty, $name:expr, $ops:expr) => {{
+        let values: Vec<SumLen> = (0..N as i64).map(SumLen::from).collect();
+        let mut tree: $tree = values.iter().copied().collect();
+        let start = Instant::now();
+        for (i, &(l, r, v)) in $ops.iter().enumerate() {
+            if i % 2 == 0 {
+                tree.apply(l..r, &v);
+            } else {
+                black_box(tree.query(l..r));
+            }
+        }
+        report($name, start.elapsed());
+    }};
+}
+
+/// Alternates range additions and "is there a k" searches.
+macro_rules! bench_find_first {
+    ($tree:ty, $name:expr, $ops:expr) => {{
+        let values: Vec<MinMaxValue> = (0..N)
+            .map(|i| MinMaxValue::from((i % 100) as i64))
+            .collect();
+        let mut tree: $tree = values.iter().copied().collect();
+        let start = Instant::now();
+        for (i, &(l, r, v)) in $ops.iter().enumerate() {
+            if i % 2 == 0 {
+                tree.apply(l..r, &v);
+            } else {
+                let k = v * 10;
+                black_box(tree.find_first(l..r, |node| node.min <= k && k <= node.max));
+            }
+        }
+        report($name, start.elapsed());
+    }};
+}
+
+fn main() {
+    println!("{N} elements, {OPS} operations per run");
+    let ops = operations(31);
+
+    bench_sum_add!(LazySegTree<Sum, Add>, "sum + add / recursive", ops);
+    bench_sum_add!(IterLazySegTree<Sum, Add>, "sum + add / iterative", ops);
+    bench_find_first!(LazySegTree<MinMax, Add>, "min/max find_first + add / recursive", ops);
+    bench_find_first!(IterLazySegTree<MinMax, Add>, "min/max find_first + add / iterative", ops);
+}
@@ -0,0 +1,282 @@
+//! Static coverage index over a set of inclusive segments.
+//!
+//! [`CoverageIndex`] backs `problem2::Backend::PositionLists` and answers
+//! witness and analytics queries on the k-fold overlap of the segments.
+
+use std::collections::{BTreeMap, HashMap};
+use std::ops::RangeInclusive;
+
+use crate::lazy_segtree::{MinMax, MinMaxValue};
+use crate::problem1::ArgMax;
+use crate::problem2::{breakpoints, interval_coverage};
+use crate::sparse_table::SparseTable;
+
+/// Coverage of `0..=u64::MAX` by a fixed set of segments, indexed by value.
+///
+/// The coordinates are split into the O(n) elementary intervals delimited by
+/// every `L` and `R + 1`; coverage is constant inside each of them. For every
+/// coverage value the index keeps the sorted list of its intervals and the
+/// prefix sums of their lengths, so the witness queries below each cost one
+/// or two binary searches.
+///
+/// Segments and query ranges are inclusive (`L..=R`); a query range with
+/// `start > end` is empty. Segments built with [`CoverageIndex::weighted`]
+/// add their weight instead of `1`, so "coverage" is the total weight of the
+/// segments containing a position and may be negative.
+///
+/// # Examples
+/// ```
+/// use handson2::coverage_index::CoverageIndex;
+///
+/// let index = CoverageIndex::new(&[0..=4, 1..=3, 10..=u64::MAX]);
+/// assert_eq!(index.coverage_at(2), 2);
+/// assert_eq!(index.first_with(0..=9, 1), Some(0));
+/// assert_eq!(index.count_with(0..=9, 0), 5);
+/// assert_eq!(index.positions_with(0..=9, 1).collect::<Vec<_>>(), vec![0, 4]);
+/// assert_eq!(index.max_coverage(0..=9).map(|max| (max.value, max.count)), Some((2, 3)));
+/// assert_eq!(index.length_at_least(0..=9, 1), 5);
+///
+/// let weighted = CoverageIndex::weighted(&[(0..=4, 3), (2..=6, -5)]);
+/// assert_eq!(weighted.coverage_at(3), -2);
+/// assert_eq!(weighted.first_with(0..=9, -5), Some(5));
+/// ```
+#[derive(Clone, Debug)]
+pub struct CoverageIndex {
+    /// Start of each elementary interval; `points[0] == 0`.
+    points: Vec<u64>,
+    /// Coverage of each elementary interval.
+    coverage: Vec<i64>,
+    /// Intervals holding each coverage value.
+    runs: HashMap<i64, Runs>,
+    /// Min/max of every range of intervals, in O(1).
+    extremes: SparseTable<MinMax>,
+}
+
+/// Extreme coverage of a range and where it occurs (see
+/// [`CoverageIndex::max_coverage`]): the [`ArgMax`] of `u64` positions,
+/// whose count can reach 2⁶⁴.
+pub type Extremum = ArgMax<i64, u64, u128>;
+
+/// Sorted intervals with the same coverage.
+#[derive(Clone, Debug)]
+struct Runs {
+    intervals: Vec<usize>,
+    /// `lengths[t]` = total length of the first `t` intervals.
+    lengths: Vec<u128>,
+}
+
+impl CoverageIndex {
+    /// Builds the index in O(n log n).
+    ///
+    /// # Panics
+    /// Panics if a segment has `L > R`.
+    pub fn new(segments: &[RangeInclusive<u64>]) -> Self {
+        segments.iter().cloned().collect()
+    }
+
+    /// Builds the index over `(segment, weight)` pairs in O(n log n).
+    ///
+    /// # Panics
+    /// Panics if a segment has `L > R`, or if the coverage of a position
+    /// does not fit in `i64`.
+    pub fn weighted(segments: &[(RangeInclusive<u64>, i64)]) -> Self {
+        segments.iter().cloned().collect()
+    }
+
+    /// Returns the number of segments covering `x` (their total weight).
+    pub fn coverage_at(&self, x: u64) -> i64 {
+        self.coverage[self.locate(x)]
+    }
+
+    /// Returns the leftmost `x` in `range` covered by exactly `k` segments.
+    pub fn first_with(&self, range: RangeInclusive<u64>, k: i64) -> Option<u64> {
+        self.runs_with(range, k).next().map(|run| *run.start())
+    }
+
+    /// Returns the rightmost `x` in `range` covered by exactly `k` segments.
+    pub fn last_with(&self, range: RangeInclusive<u64>, k: i64) -> Option<u64> {
+        let (runs, first, last) = self.matching(&range, k)?;
+        (first < last).then(|| self.end(runs.intervals[last - 1]).min(*range.end()))
+    }
+
+    /// Returns the maximum coverage of `range` with the leftmost and
+    /// rightmost positions attaining it and their count, or `None` if the
+    /// range is empty. Runs in O(log n) (the value itself in O(1)).
+    pub fn max_coverage(&self, range: RangeInclusive<u64>) -> Option<Extremum> {
+        let value = self.range_extremes(&range)?.max;
+        Some(self.extremum(range, value))
+    }
+
+    /// Returns the minimum coverage of `range`, like
+    /// [`max_coverage`](Self::max_coverage).
+    pub fn min_coverage(&self, range: RangeInclusive<u64>) -> Option<Extremum> {
+        let value = self.range_extremes(&range)?.min;
+        Some(self.extremum(range, value))
+    }
+
+    /// Returns, for every coverage value occurring in `range`, how many
+    /// positions of `range` hold it. Runs in O(d log n) for `d` distinct
+    /// coverage values overall.
+    pub fn histogram(&self, range: RangeInclusive<u64>) -> BTreeMap<i64, u128> {
+        self.runs
+            .keys()
+            .map(|&k| (k, self.count_with(range.clone(), k)))
+            .filter(|&(_, count)| count > 0)
+            .collect()
+    }
+
+    /// Returns how many positions of `range` are covered by at least `k`
+    /// segments. Runs in O(d log n) for `d` distinct coverage values overall.
+    pub fn length_at_least(&self, range: RangeInclusive<u64>, k: i64) -> u128 {
+        self.runs
+            .keys()
+            .filter(|&&value| value >= k)
+            .map(|&value| self.count_with(range.clone(), value))
+            .sum()
+    }
+
+    /// Returns how many positions of `range` are covered by exactly `k`
+    /// segments (up to 2⁶⁴, hence `u128`).
+    pub fn count_with(&self, range: RangeInclusive<u64>, k: i64) -> u128 {
+        let Some((runs, first, last)) = self.matching(&range, k) else {
+            return 0;
+        };
+        if first == last {
+            return 0;
+        }
+
+        // Whole intervals, minus the parts sticking out of the range.
+        let (start, end) = range.into_inner();
+        let mut count = runs.lengths[last] - runs.lengths[first];
+        count -= u128::from(start.saturating_sub(self.start(runs.intervals[first])));
+        count -= u128::from(self.end(runs.intervals[last - 1]).saturating_sub(end));
+        count
+    }
+
+    /// Returns the positions in `range` covered by exactly `k` segments as
+    /// ranges of consecutive positions, from left to right. Two returned
+    /// ranges may be adjacent when a segment ends right where another starts.
+    pub fn runs_with(
+        &self,
+        range: RangeInclusive<u64>,
+        k: i64,
+    ) -> impl Iterator<Item = RangeInclusive<u64>> + '_ {
+        let intervals = self
+            .matching(&range, k)
+            .map_or(&[][..], |(runs, first, last)| &runs.intervals[first..last]);
+        let (start, end) = range.into_inner();
+        intervals
+            .iter()
+            .map(move |&interval| self.start(interval).max(start)..=self.end(interval).min(end))
+    }
+
+    /// Returns every position in `range` covered by exactly `k` segments,
+    /// from left to right.
+    pub fn positions_with(
+        &self,
+        range: RangeInclusive<u64>,
+        k: i64,
+    ) -> impl Iterator<Item = u64> + '_ {
+        self.runs_with(range, k).flatten()
+    }
+
+    /// Returns the runs of `k` and the slice `first..last` of them that
+    /// overlaps `range`, or `None` if there is none.
+    fn matching(&self, range: &RangeInclusive<u64>, k: i64) -> Option<(&Runs, usize, usize)> {
+        if range.is_empty() {
+            return None;
+        }
+        let runs = self.runs.get(&k)?;
+        let (start, end) = (self.locate(*range.start()), self.locate(*range.end()));
+        let first = runs.intervals.partition_point(|&t| t < start);
+        let last = runs.intervals.partition_point(|&t| t <= end);
+        Some((runs, first, last))
+    }
+
+    /// Min/max coverage over the intervals overlapping `range`, or `None` if
+    /// it is empty.
+    fn range_extremes(&self, range: &RangeInclusive<u64>) -> Option<MinMaxValue> {
+        if range.is_empty() {
+            return None;
+        }
+        let intervals = self.locate(*range.start())..self.locate(*range.end()) + 1;
+        Some(self.extremes.query(intervals))
+    }
+
+    /// Describes where `value`, which occurs in `range`, occurs.
+    fn extremum(&self, range: RangeInclusive<u64>, value: i64) -> Extremum {
+        Extremum {
+            value,
+            first: self
+                .first_with(range.clone(), value)
+                .expect("Extreme value occurs in the range"),
+            last: self
+                .last_with(range.clone(), value)
+                .expect("Extreme value occurs in the range"),
+            count: self.count_with(range, value),
+        }
+    }
+
+    /// Returns the elementary interval containing `x`.
+    fn locate(&self, x: u64) -> usize {
+        self.points.partition_point(|&point| point <= x) - 1
+    }
+
+    /// First position of elementary interval `t`.
+    fn start(&self, t: usize) -> u64 {
+        self.points[t]
+    }
+
+    /// Last position of elementary interval `t`.
+    fn end(&self, t: usize) -> u64 {
+        self.points.get(t + 1).map_or(u64::MAX, |next| next - 1)
+    }
+}
+
+impl FromIterator<RangeInclusive<u64>> for CoverageIndex {
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<u64>>>(iter: I) -> Self {
+        iter.into_iter().map(|seg| (seg, 1)).collect()
+    }
+}
+
+impl FromIterator<(RangeInclusive<u64>, i64)> for CoverageIndex {
+    fn from_iter<I: IntoIterator<Item = (RangeInclusive<u64>, i64)>>(iter: I) -> Self {
+        let segments: Vec<(u64, u64, i64)> = iter
+            .into_iter()
+            .map(|(seg, weight)| {
+                assert!(!seg.is_empty(), "Segment {seg:?} is empty");
+                let (left, right) = seg.into_inner();
+                (left, right, weight)
+            })
+            .collect();
+
+        // Intervals must span every position, starting at 0
+        let mut points = breakpoints(segments.iter().map(|&(left, right, _)| (left, right)));
+        if points.first() != Some(&0) {
+            points.insert(0, 0);
+        }
+        let coverage = interval_coverage(&points, segments)
+            .unwrap_or_else(|x| panic!("Coverage of position {x} does not fit in i64"));
+
+        let extremes = coverage.iter().copied().map(MinMaxValue::from).collect();
+
+        let mut index = Self {
+            points,
+            coverage,
+            runs: HashMap::new(),
+            extremes,
+        };
+        // Scanning in order keeps every list sorted.
+        for t in 0..index.coverage.len() {
+            let length = u128::from(index.end(t) - index.start(t)) + 1;
+            let runs = index.runs.entry(index.coverage[t]).or_insert_with(|| Runs {
+                intervals: Vec::new(),
+                lengths: vec![0],
+            });
+            let total = runs.lengths[runs.intervals.len()] + length;
+            runs.intervals.push(t);
+            runs.lengths.push(total);
+        }
+        index
+    }
+}
@@ -0,0 +1,394 @@
+//! Fenwick trees (binary indexed trees) for prefix sums under updates.
+//!
+//! A Fenwick tree stores, at 1-based position `i`, the sum of the `i & -i`
+//! elements ending at `i`. A prefix sum adds O(log n) of these blocks and a
+//! point update touches O(log n) of them, with a single array of `n + 1`
+//! integers and no recursion. When only sums are needed this is lighter than
+//! a segment tree; it cannot answer min/max or pruned searches.
+//!
+//! All ranges are 0-based and half-open (`start..end`).
+
+use std::ops::Range;
+
+use crate::check_range;
+
+/// Point update, prefix / range sum.
+///
+/// # Examples
+/// ```
+/// use handson2::fenwick::FenwickTree;
+///
+/// let mut tree: FenwickTree = [3, 1, 4, 1, 5].into_iter().collect();
+/// tree.add(2, 10);
+/// assert_eq!(tree.prefix_sum(3), 18);
+/// assert_eq!(tree.sum(1..4), 16);
+/// assert_eq!(tree.lower_bound(18), Some(3));
+/// ```
+#[derive(Clone, Debug)]
+pub struct FenwickTree {
+    /// 1-based blocks; `tree[0]` is unused.
+    tree: Vec<i64>,
+}
+
+impl FenwickTree {
+    /// Creates a tree of `len` zeros.
+    pub fn new(len: usize) -> Self {
+        Self {
+            tree: vec![0; len + 1],
+        }
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.tree.len() - 1
+    }
+
+    /// Returns `true` if the tree holds no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Applies `A[index] += delta` in O(log n).
+    ///
+    /// # Panics
+    /// Panics if `index >= len()`.
+    pub fn add(&mut self, index: usize, delta: i64) {
+        assert!(
+            index < self.len(),
+            "Index {index} is out of bounds for length {}",
+            self.len()
+        );
+        let mut i = index + 1;
+        while i < self.tree.len() {
+            self.tree[i] += delta;
+            i += lowest_bit(i);
+        }
+    }
+
+    /// Returns `A[0] + … + A[end - 1]` in O(log n).
+    ///
+    /// # Panics
+    /// Panics if `end > len()`.
+    pub fn prefix_sum(&self, end: usize) -> i64 {
+        assert!(
+            end <= self.len(),
+            "Prefix 0..{end} is out of bounds for length {}",
+            self.len()
+        );
+        let mut sum = 0;
+        let mut i = end;
+        while i > 0 {
+            sum += self.tree[i];
+            i -= lowest_bit(i);
+        }
+        sum
+    }
+
+    /// Returns the sum of `range` (`0` if it is empty).
+    ///
+    /// # Panics
+    /// Panics if `start > end` or `end > len()`.
+    pub fn sum(&self, range: Range<usize>) -> i64 {
+        check_range(&range, self.len());
+        self.prefix_sum(range.end) - self.prefix_sum(range.start)
+    }
+
+    /// Returns the smallest `end` with `prefix_sum(end) >= target`, or
+    /// `None` if even the whole array sums to less.
+    ///
+    /// Descends the implicit tree in O(log n). The prefix sums must be
+    /// non-decreasing, i.e. every element non-negative.
+    pub fn lower_bound(&self, target: i64) -> Option<usize> {
+        if target <= 0 {
+            return Some(0);
+        }
+
+        // Largest `end` with prefix_sum(end) < target, one bit at a time
+        let mut end = 0;
+        let mut remaining = target;
+        let mut step = self.len().checked_next_power_of_two()?;
+        while step > 0 {
+            let next = end + step;
+            if next < self.tree.len() && self.tree[next] < remaining {
+                end = next;
+                remaining -= self.tree[next];
+            }
+            step /= 2;
+        }
+        (end < self.len()).then_some(end + 1)
+    }
+}
+
+impl FromIterator<i64> for FenwickTree {
+    /// Builds the tree in O(n) by pushing every block into its parent.
+    fn from_iter<I: IntoIterator<Item = i64>>(iter: I) -> Self {
+        let mut tree: Vec<i64> = [0].into_iter().chain(iter).collect();
+        for i in 1..tree.len() {
+            let parent = i + lowest_bit(i);
+            if parent < tree.len() {
+                tree[parent] += tree[i];
+            }
+        }
+        Self { tree }
+    }
+}
+
+/// Range add, point query.
+///
+/// Stores the difference array in a [`FenwickTree`]: adding to a range
+/// changes two differences, and an element is the prefix sum of the
+/// differences up to it.
+///
+/// # Examples
+/// ```
+/// use handson2::fenwick::RangeAddFenwick;
+///
+/// let mut tree = RangeAddFenwick::new(6);
+/// tree.add(1..4, 2);
+/// tree.add(3..6, -1);
+/// assert_eq!((0..6).map(|i| tree.get(i)).collect::<Vec<_>>(), vec![0, 2, 2, 1, -1, -1]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct RangeAddFenwick {
+    diff: FenwickTree,
+}
+
+impl RangeAddFenwick {
+    /// Creates a tree of `len` zeros.
+    pub fn new(len: usize) -> Self {
+        Self {
+            diff: FenwickTree::new(len + 1),
+        }
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.diff.len() - 1
+    }
+
+    /// Returns `true` if the tree holds no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Applies `A[k] += delta` for every `k` in `range`, in O(log n).
+    ///
+    /// # Panics
+    /// Panics if `start > end` or `end > len()`.
+    pub fn add(&mut self, range: Range<usize>, delta: i64) {
+        check_range(&range, self.len());
+        self.diff.add(range.start, delta);
+        self.diff.add(range.end, -delta);
+    }
+
+    /// Returns `A[index]` in O(log n).
+    ///
+    /// # Panics
+    /// Panics if `index >= len()`.
+    pub fn get(&self, index: usize) -> i64 {
+        assert!(
+            index < self.len(),
+            "Index {index} is out of bounds for length {}",
+            self.len()
+        );
+        self.diff.prefix_sum(index + 1)
+    }
+}
+
+/// Range add, range sum.
+///
+/// With `D` the difference array, `A[0] + … + A[e - 1]` equals
+/// `e · ΣD[i] - Σi · D[i]` over `i < e`, so two [`FenwickTree`]s over `D`
+/// and `i · D` give every prefix sum.
+///
+/// # Examples
+/// ```
+/// use handson2::fenwick::RangeSumFenwick;
+///
+/// let mut tree: RangeSumFenwick = [1, 2, 3, 4].into_iter().collect();
+/// tree.add(1..3, 10);
+/// assert_eq!(tree.sum(0..4), 30);
+/// assert_eq!(tree.sum(2..3), 13);
+/// ```
+#[derive(Clone, Debug)]
+pub struct RangeSumFenwick {
+    /// Fenwick tree over `D[i]`.
+    diff: FenwickTree,
+    /// Fenwick tree over `i · D[i]`.
+    weighted: FenwickTree,
+}
+
+impl RangeSumFenwick {
+    /// Creates a tree of `len` zeros.
+    pub fn new(len: usize) -> Self {
+        Self {
+            diff: FenwickTree::new(len + 1),
+            weighted: FenwickTree::new(len + 1),
+        }
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.diff.len() - 1
+    }
+
+    /// Returns `true` if the tree holds no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Applies `A[k] += delta` for every `k` in `range`, in O(log n).
+    ///
+    /// # Panics
+    /// Panics if `start > end` or `end > len()`.
+    pub fn add(&mut self, range: Range<usize>, delta: i64) {
+        check_range(&range, self.len());
+        for (index, delta) in [(range.start, delta), (range.end, -delta)] {
+            self.diff.add(index, delta);
+            self.weighted.add(index, index as i64 * delta);
+        }
+    }
+
+    /// Returns `A[0] + … + A[end - 1]` in O(log n).
+    ///
+    /// # Panics
+    /// Panics if `end > len()`.
+    pub fn prefix_sum(&self, end: usize) -> i64 {
+        assert!(
+            end <= self.len(),
+            "Prefix 0..{end} is out of bounds for length {}",
+            self.len()
+        );
+        end as i64 * self.diff.prefix_sum(end) - self.weighted.prefix_sum(end)
+    }
+
+    /// Returns the sum of `range` (`0` if it is empty).
+    ///
+    /// # Panics
+    /// Panics if `start > end` or `end > len()`.
+    pub fn sum(&self, range: Range<usize>) -> i64 {
+        check_range(&range, self.len());
+        self.prefix_sum(range.end) - self.prefix_sum(range.start)
+    }
+}
+
+impl FromIterator<i64> for RangeSumFenwick {
+    fn from_iter<I: IntoIterator<Item = i64>>(iter: I) -> Self {
+        let values: Vec<i64> = iter.into_iter().collect();
+        let mut tree = Self::new(values.len());
+        for (index, &value) in values.iter().enumerate() {
+            tree.add(index..index + 1, value);
+        }
+        tree
+    }
+}
+
+/// 2D point update, rectangle sum.
+///
+/// Every row block holds a Fenwick tree over the columns, so both updates
+/// and queries cost O(log r · log c).
+///
+/// # Examples
+/// ```
+/// use handson2::fenwick::FenwickTree2D;
+///
+/// let mut grid = FenwickTree2D::new(3, 4);
+/// grid.add(0, 0, 1);
+/// grid.add(1, 2, 5);
+/// grid.add(2, 3, 7);
+/// assert_eq!(grid.sum(0..2, 0..3), 6);
+/// assert_eq!(grid.sum(1..3, 2..4), 12);
+/// ```
+#[derive(Clone, Debug)]
+pub struct FenwickTree2D {
+    rows: usize,
+    cols: usize,
+    /// `(rows + 1) × (cols + 1)` blocks, row-major; row and column 0 unused.
+    tree: Vec<i64>,
+}
+
+impl FenwickTree2D {
+    /// Creates a `rows × cols` grid of zeros.
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self {
+            rows,
+            cols,
+            tree: vec![0; (rows + 1) * (cols + 1)],
+        }
+    }
+
+    /// Returns the number of rows.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the number of columns.
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Applies `A[row][col] += delta` in O(log r · log c).
+    ///
+    /// # Panics
+    /// Panics if `row >= rows()` or `col >= cols()`.
+    pub fn add(&mut self, row: usize, col: usize, delta: i64) {
+        assert!(
+            row < self.rows && col < self.cols,
+            "Cell ({row}, {col}) is out of bounds for {}×{}",
+            self.rows,
+            self.cols
+        );
+        let mut i = row + 1;
+        while i <= self.rows {
+            let mut j = col + 1;
+            while j <= self.cols {
+                self.tree[i * (self.cols + 1) + j] += delta;
+                j += lowest_bit(j);
+            }
+            i += lowest_bit(i);
+        }
+    }
+
+    /// Returns the sum of the cells in rows `0..row_end` and columns
+    /// `0..col_end`.
+    ///
+    /// # Panics
+    /// Panics if `row_end > rows()` or `col_end > cols()`.
+    pub fn prefix_sum(&self, row_end: usize, col_end: usize) -> i64 {
+        assert!(
+            row_end <= self.rows && col_end <= self.cols,
+            "Prefix 0..{row_end} × 0..{col_end} is out of bounds for {}×{}",
+            self.rows,
+            self.cols
+        );
+        let mut sum = 0;
+        let mut i = row_end;
+        while i > 0 {
+            let mut j = col_end;
+            while j > 0 {
+                sum += self.tree[i * (self.cols + 1) + j];
+                j -= lowest_bit(j);
+            }
+            i -= lowest_bit(i);
+        }
+        sum
+    }
+
+    /// Returns the sum of the cells in `rows × cols` (`0` if it is empty).
+    ///
+    /// # Panics
+    /// Panics if either range has `start > end` or exceeds the grid.
+    pub fn sum(&self, rows: Range<usize>, cols: Range<usize>) -> i64 {
+        check_range(&rows, self.rows);
+        check_range(&cols, self.cols);
+        self.prefix_sum(rows.end, cols.end)
+            - self.prefix_sum(rows.start, cols.end)
+            - self.prefix_sum(rows.end, cols.start)
+            + self.prefix_sum(rows.start, cols.start)
+    }
+}
+
+/// Lowest set bit of `i`: the length of the block stored at `i`.
+fn lowest_bit(i: usize) -> usize {
+    i & i.wrapping_neg()
+}
@@ -0,0 +1,439 @@
+//! Historic-maximum variant of the Problem 1 Segment Tree Beats.
+//!
+//! Besides the current values, [`HistoricMaxTree`] remembers for every
+//! position the largest value it ever held (including its initial value)
+//! under range chmin and range add updates.
+
+use std::ops::Range;
+
+use crate::journal::{Checkpoint, Journal};
+
+/// Aggregate of a segment plus its pending tags.
+///
+/// `second_max` is `None` when every element of the segment is equal. All
+/// fields are meaningless when `max_count == 0` (empty segment).
+#[derive(Clone, Copy, Debug)]
+struct Node {
+    max_value: i64,
+    second_max: Option<i64>,
+    max_count: usize,
+    /// Wrapping sum: exact whenever the true sum fits in `i64`.
+    sum: i64,
+    /// Largest value ever held by an element of the segment.
+    historic_max: i64,
+    /// Pending addition for the elements equal to `max_value`.
+    add_max: i64,
+    /// Largest prefix sum reached by `add_max` since the last push.
+    peak_add_max: i64,
+    /// Pending addition for all the other elements.
+    add_other: i64,
+    /// Largest prefix sum reached by `add_other` since the last push.
+    peak_add_other: i64,
+}
+
+impl Node {
+    /// Create a node from a single value.
+    fn from_single(value: i64) -> Self {
+        Self {
+            max_value: value,
+            second_max: None,
+            max_count: 1,
+            sum: value,
+            historic_max: value,
+            ..Self::empty()
+        }
+    }
+
+    /// Create an empty node (used for initialization and in special cases).
+    fn empty() -> Self {
+        Self {
+            max_value: 0,
+            second_max: None,
+            max_count: 0,
+            sum: 0,
+            historic_max: 0,
+            add_max: 0,
+            peak_add_max: 0,
+            add_other: 0,
+            peak_add_other: 0,
+        }
+    }
+
+    /// Merge two child nodes into a parent node (without tags).
+    fn merge(left: Node, right: Node) -> Node {
+        // If one node is empty, return the other
+        if left.max_count == 0 {
+            return Self::untagged(right);
+        }
+        if right.max_count == 0 {
+            return Self::untagged(left);
+        }
+
+        // `None < Some(_)`, so `max` skips missing second maximums
+        let (max_value, second_max, max_count) = if left.max_value == right.max_value {
+            (
+                left.max_value,
+                left.second_max.max(right.second_max),
+                left.max_count + right.max_count,
+            )
+        } else if left.max_value > right.max_value {
+            (
+                left.max_value,
+                left.second_max.max(Some(right.max_value)),
+                left.max_count,
+            )
+        } else {
+            (
+                right.max_value,
+                right.second_max.max(Some(left.max_value)),
+                right.max_count,
+            )
+        };
+
+        Node {
+            max_value,
+            second_max,
+            max_count,
+            sum: left.sum.wrapping_add(right.sum),
+            historic_max: left.historic_max.max(right.historic_max),
+            ..Self::empty()
+        }
+    }
+
+    /// Copy of `node` with its pending tags cleared.
+    fn untagged(node: Node) -> Node {
+        Node {
+            add_max: 0,
+            peak_add_max: 0,
+            add_other: 0,
+            peak_add_other: 0,
+            ..node
+        }
+    }
+}
+
+/// Segment Tree Beats with historic maximums.
+///
+/// Supports range chmin and range add updates together with range max, sum
+/// and historic max queries. All ranges are 0-based and half-open
+/// (`start..end`). Updates run in amortized O(log² n), queries in O(log n).
+///
+/// Every node keeps two pairs of lazy tags: one for the elements equal to
+/// its maximum and one for the others. Each pair stores the pending addition
+/// and the largest value that addition reached since the last push, so a
+/// child can recover the peak it would have seen had updates been applied
+/// eagerly.
+///
+/// Values may span all of `i64` (no sentinel values; sums wrap and are exact
+/// whenever the result fits). The additions pending on a node must fit in
+/// `i64`; a chmin whose drop does not fit descends to the leaves instead.
+///
+/// Updates can be undone with [`checkpoint`](Self::checkpoint) and
+/// [`rollback`](Self::rollback).
+///
+/// # Panics
+/// Every range method panics if `start > end` or `end > len()`.
+///
+/// # Examples
+/// ```
+/// use handson2::historic_max::HistoricMaxTree;
+///
+/// let mut tree = HistoricMaxTree::new(&[5, 1, 4]);
+/// tree.chmin(0..3, 2);
+/// tree.add(1..2, 3);
+/// assert_eq!(tree.max(0..3), Some(4));
+/// assert_eq!(tree.historic_max(0..1), Some(5));
+/// ```
+#[derive(Clone, Debug)]
+pub struct HistoricMaxTree {
+    n: usize,
+    tree: Vec<Node>,
+    journal: Journal<Node>,
+}
+
+impl HistoricMaxTree {
+    /// Builds the tree over a copy of `values` in O(n).
+    pub fn new(values: &[i64]) -> Self {
+        let n = values.len();
+        let size = 4 * n.max(1);
+        let mut st = Self {
+            n,
+            tree: vec![Node::empty(); size],
+            journal: Journal::new(),
+        };
+        if n > 0 {
+            st.build(1, 0, n - 1, values);
+        }
+        st
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    /// Returns `true` if the tree holds no elements.
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// Applies `A[k] = min(A[k], x)` for every `k` in `range`.
+    pub fn chmin(&mut self, range: Range<usize>, x: i64) {
+        if let Some((ql, qr)) = self.bounds(range) {
+            self.range_chmin_rec(1, 0, self.n - 1, ql, qr, x);
+        }
+    }
+
+    /// Applies `A[k] = A[k] + value` for every `k` in `range`.
+    pub fn add(&mut self, range: Range<usize>, value: i64) {
+        if let Some((ql, qr)) = self.bounds(range) {
+            self.range_add_rec(1, 0, self.n - 1, ql, qr, value);
+        }
+    }
+
+    /// Returns the current maximum of `range`, or `None` if it is empty.
+    pub fn max(&mut self, range: Range<usize>) -> Option<i64> {
+        self.query(range).map(|node| node.max_value)
+    }
+
+    /// Returns the current sum of `range` (`0` if it is empty).
+    ///
+    /// The sum wraps around on overflow, so it is exact whenever the true sum
+    /// fits in `i64`, even if partial sums do not.
+    pub fn sum(&mut self, range: Range<usize>) -> i64 {
+        self.query(range).map_or(0, |node| node.sum)
+    }
+
+    /// Returns the largest value ever held by a position of `range`, or
+    /// `None` if it is empty.
+    pub fn historic_max(&mut self, range: Range<usize>) -> Option<i64> {
+        self.query(range).map(|node| node.historic_max)
+    }
+
+    /// Opens a checkpoint at the current state; see [`journal`](crate::journal).
+    pub fn checkpoint(&mut self) -> Checkpoint {
+        self.journal.checkpoint()
+    }
+
+    /// Restores the state at `checkpoint`; see [`journal`](crate::journal).
+    ///
+    /// # Panics
+    /// Panics if `checkpoint` was already released.
+    pub fn rollback(&mut self, checkpoint: Checkpoint) {
+        self.journal
+            .rollback(checkpoint, |index, node| self.tree[index] = node);
+    }
+
+    /// Keeps the changes made since `checkpoint`; see [`journal`](crate::journal).
+    ///
+    /// # Panics
+    /// Panics if `checkpoint` was already released.
+    pub fn commit(&mut self, checkpoint: Checkpoint) {
+        self.journal.commit(checkpoint);
+    }
+
+    /// Validates a half-open range and converts it to inclusive bounds,
+    /// or `None` if it is empty.
+    fn bounds(&self, range: Range<usize>) -> Option<(usize, usize)> {
+        crate::check_range(&range, self.n);
+        (!range.is_empty()).then(|| (range.start, range.end - 1))
+    }
+
+    /// Returns the merged node describing `range`, or `None` if it is empty.
+    fn query(&mut self, range: Range<usize>) -> Option<Node> {
+        let (ql, qr) = self.bounds(range)?;
+        Some(self.range_query_rec(1, 0, self.n - 1, ql, qr))
+    }
+
+    fn build(&mut self, index: usize, left: usize, right: usize, values: &[i64]) {
+        // Leaf node
+        if left == right {
+            self.tree[index] = Node::from_single(values[left]);
+            return;
+        }
+
+        let mid = (left + right) / 2;
+        let left_child = index * 2;
+        let right_child = left_child + 1;
+
+        self.build(left_child, left, mid, values);
+        self.build(right_child, mid + 1, right, values);
+
+        self.tree[index] = Node::merge(self.tree[left_child], self.tree[right_child]);
+    }
+
+    /// Records node `index` on the journal, then overwrites it with `node`.
+    fn set(&mut self, index: usize, node: Node) {
+        if self.journal.is_recording() {
+            self.journal.record(index, self.tree[index]);
+        }
+        self.tree[index] = node;
+    }
+
+    /// Apply pending additions to a node covering `len` positions.
+    ///
+    /// `add_max`/`peak_add_max` go to the elements equal to the maximum,
+    /// `add_other`/`peak_add_other` to the others.
+    fn apply_tags(
+        &mut self,
+        index: usize,
+        len: usize,
+        (add_max, peak_add_max): (i64, i64),
+        (add_other, peak_add_other): (i64, i64),
+    ) {
+        let mut node = self.tree[index];
+        node.sum = node
+            .sum
+            .wrapping_add(add_max.wrapping_mul(node.max_count as i64))
+            .wrapping_add(add_other.wrapping_mul((len - node.max_count) as i64));
+        node.historic_max = node.historic_max.max(node.max_value + peak_add_max);
+        node.max_value += add_max;
+        node.second_max = node.second_max.map(|second| second + add_other);
+
+        // Compose with the node's own pending tags.
+        node.peak_add_max = node.peak_add_max.max(node.add_max + peak_add_max);
+        node.add_max += add_max;
+        node.peak_add_other = node.peak_add_other.max(node.add_other + peak_add_other);
+        node.add_other += add_other;
+        self.set(index, node);
+    }
+
+    /// Push the parent's pending tags down to children.
+    fn push_down(&mut self, index: usize, left: usize, right: usize) {
+        let node = self.tree[index];
+        let mid = (left + right) / 2;
+        let left_child = index * 2;
+        let right_child = left_child + 1;
+
+        // Children holding the (pre-tag) maximum receive the max tags.
+        let child_max = self.tree[left_child]
+            .max_value
+            .max(self.tree[right_child].max_value);
+        let max_tags = (node.add_max, node.peak_add_max);
+        let other_tags = (node.add_other, node.peak_add_other);
+
+        for (child, len) in [(left_child, mid - left + 1), (right_child, right - mid)] {
+            if self.tree[child].max_value == child_max {
+                self.apply_tags(child, len, max_tags, other_tags);
+            } else {
+                self.apply_tags(child, len, other_tags, other_tags);
+            }
+        }
+
+        self.set(index, Node::untagged(node));
+    }
+
+    fn range_chmin_rec(
+        &mut self,
+        index: usize,
+        left: usize,
+        right: usize,
+        ql: usize,
+        qr: usize,
+        x: i64,
+    ) {
+        let node = self.tree[index];
+
+        // No overlap or nothing to reduce
+        if right < ql || qr < left || node.max_value <= x {
+            return;
+        }
+
+        // Fully covered and only the maximums change: a negative add on them
+        if ql <= left && right <= qr && node.second_max < Some(x) {
+            if let Some(delta) = x.checked_sub(node.max_value) {
+                self.apply_tags(index, right - left + 1, (delta, delta), (0, 0));
+                return;
+            }
+            // The add does not fit in `i64`: assign leaves directly
+            if left == right {
+                self.set(
+                    index,
+                    Node {
+                        max_value: x,
+                        sum: x,
+                        ..node
+                    },
+                );
+                return;
+            }
+        }
+
+        // Otherwise, push constraints down before recursion
+        self.push_down(index, left, right);
+        let mid = (left + right) / 2;
+        let left_child = index * 2;
+        let right_child = left_child + 1;
+
+        self.range_chmin_rec(left_child, left, mid, ql, qr, x);
+        self.range_chmin_rec(right_child, mid + 1, right, ql, qr, x);
+
+        self.set(
+            index,
+            Node::merge(self.tree[left_child], self.tree[right_child]),
+        );
+    }
+
+    fn range_add_rec(
+        &mut self,
+        index: usize,
+        left: usize,
+        right: usize,
+        ql: usize,
+        qr: usize,
+        value: i64,
+    ) {
+        // No overlap
+        if right < ql || qr < left {
+            return;
+        }
+
+        // Fully covered: tag the node
+        if ql <= left && right <= qr {
+            self.apply_tags(index, right - left + 1, (value, value), (value, value));
+            return;
+        }
+
+        self.push_down(index, left, right);
+        let mid = (left + right) / 2;
+        let left_child = index * 2;
+        let right_child = left_child + 1;
+
+        self.range_add_rec(left_child, left, mid, ql, qr, value);
+        self.range_add_rec(right_child, mid + 1, right, ql, qr, value);
+
+        self.set(
+            index,
+            Node::merge(self.tree[left_child], self.tree[right_child]),
+        );
+    }
+
+    fn range_query_rec(
+        &mut self,
+        index: usize,
+        left: usize,
+        right: usize,
+        ql: usize,
+        qr: usize,
+    ) -> Node {
+        // No overlap
+        if right < ql || qr < left {
+            return Node::empty();
+        }
+        // Fully covered
+        if ql <= left && right <= qr {
+            return Node::untagged(self.tree[index]);
+        }
+
+        // Partial overlap; push down and recurse
+        self.push_down(index, left, right);
+
+        let mid = (left + right) / 2;
+        let left_child = index * 2;
+        let right_child = left_child + 1;
+
+        let node_left = self.range_query_rec(left_child, left, mid, ql, qr);
+        let node_right = self.range_query_rec(right_child, mid + 1, right, ql, qr);
+        Node::merge(node_left, node_right)
+    }
+}
@@ -0,0 +1,191 @@
+//! Centered interval tree answering "which segments cover this point?".
+//!
+//! Complements the coverage structures of Problem 2: they count how many
+//! segments cover a position, [`IntervalTree`] lists them.
+
+use std::ops::RangeInclusive;
+
+#[derive(Clone, Debug)]
+struct Node {
+    center: u64,
+    /// Segments containing `center`, by increasing left endpoint.
+    by_left: Vec<usize>,
+    /// Segments containing `center`, by decreasing right endpoint.
+    by_right: Vec<usize>,
+    /// Subtree of the segments entirely before `center`.
+    left: Option<usize>,
+    /// Subtree of the segments entirely after `center`.
+    right: Option<usize>,
+}
+
+/// Static interval tree over inclusive `u64` segments.
+///
+/// Every node picks the median endpoint of its segments as `center`, keeps
+/// the segments containing it, and sends the others to the side they lie on,
+/// so the tree has O(log n) levels. Segments are reported by their index in
+/// the slice given to [`IntervalTree::new`], in no particular order.
+///
+/// # Examples
+/// ```
+/// use handson2::interval_tree::IntervalTree;
+///
+/// let tree = IntervalTree::new(&[0..=4, 1..=3, 6..=9]);
+/// let mut covering = tree.stab(2);
+/// covering.sort();
+/// assert_eq!(covering, vec![0, 1]);
+/// assert_eq!(tree.overlapping(4..=6).len(), 2);
+/// ```
+#[derive(Clone, Debug)]
+pub struct IntervalTree {
+    segments: Vec<(u64, u64)>,
+    /// Segment indices sorted by left endpoint.
+    by_start: Vec<usize>,
+    nodes: Vec<Node>,
+    root: Option<usize>,
+}
+
+impl IntervalTree {
+    /// Builds the tree in O(n log n).
+    ///
+    /// # Panics
+    /// Panics if a segment has `L > R`.
+    pub fn new(segments: &[RangeInclusive<u64>]) -> Self {
+        segments.iter().cloned().collect()
+    }
+
+    /// Returns the number of segments.
+    pub fn len(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Returns `true` if the tree holds no segments.
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// Returns the indices of the segments covering `x`, in O(log n + k)
+    /// for `k` reported segments.
+    pub fn stab(&self, x: u64) -> Vec<usize> {
+        let mut found = Vec::new();
+        self.stab_into(x, &mut found);
+        found
+    }
+
+    /// Returns the indices of the segments intersecting `range`, in
+    /// O(log n + k) for `k` reported segments (none if `range` is empty).
+    pub fn overlapping(&self, range: RangeInclusive<u64>) -> Vec<usize> {
+        let (start, end) = range.into_inner();
+        if start > end {
+            return Vec::new();
+        }
+
+        // Segments containing `start`, plus those starting inside the range:
+        // the two groups are disjoint once the first keeps `L < start`.
+        let mut found = Vec::new();
+        self.stab_into(start, &mut found);
+        found.retain(|&id| self.segments[id].0 < start);
+
+        let first = self
+            .by_start
+            .partition_point(|&id| self.segments[id].0 < start);
+        let last = self
+            .by_start
+            .partition_point(|&id| self.segments[id].0 <= end);
+        found.extend_from_slice(&self.by_start[first..last]);
+        found
+    }
+
+    /// Appends the segments covering `x` to `found`.
+    fn stab_into(&self, x: u64, found: &mut Vec<usize>) {
+        let mut current = self.root;
+        while let Some(index) = current {
+            let node = &self.nodes[index];
+            if x < node.center {
+                // Only segments starting at or before `x` can reach it
+                found.extend(
+                    node.by_left
+                        .iter()
+                        .take_while(|&&id| self.segments[id].0 <= x),
+                );
+                current = node.left;
+            } else {
+                // Only segments ending at or after `x` can reach it
+                found.extend(
+                    node.by_right
+                        .iter()
+                        .take_while(|&&id| self.segments[id].1 >= x),
+                );
+                current = if x == node.center { None } else { node.right };
+            }
+        }
+    }
+
+    /// Builds the subtree holding the segments `ids` and returns its root.
+    fn build(&mut self, mut ids: Vec<usize>) -> Option<usize> {
+        if ids.is_empty() {
+            return None;
+        }
+
+        // Median endpoint: at most half of the segments lie on each side
+        let mut endpoints: Vec<u64> = ids
+            .iter()
+            .flat_map(|&id| [self.segments[id].0, self.segments[id].1])
+            .collect();
+        let middle = endpoints.len() / 2;
+        let center = *endpoints.select_nth_unstable(middle).1;
+
+        let (mut left, mut right) = (Vec::new(), Vec::new());
+        ids.retain(|&id| {
+            let (start, end) = self.segments[id];
+            if end < center {
+                left.push(id);
+                false
+            } else if start > center {
+                right.push(id);
+                false
+            } else {
+                true
+            }
+        });
+
+        let mut by_left = ids.clone();
+        by_left.sort_unstable_by_key(|&id| self.segments[id].0);
+        let mut by_right = ids;
+        by_right.sort_unstable_by_key(|&id| std::cmp::Reverse(self.segments[id].1));
+
+        let left = self.build(left);
+        let right = self.build(right);
+        self.nodes.push(Node {
+            center,
+            by_left,
+            by_right,
+            left,
+            right,
+        });
+        Some(self.nodes.len() - 1)
+    }
+}
+
+impl FromIterator<RangeInclusive<u64>> for IntervalTree {
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<u64>>>(iter: I) -> Self {
+        let segments: Vec<(u64, u64)> = iter
+            .into_iter()
+            .map(|seg| {
+                assert!(!seg.is_empty(), "Segment {seg:?} is empty");
+                seg.into_inner()
+            })
+            .collect();
+
+        let mut by_start: Vec<usize> = (0..segments.len()).collect();
+        by_start.sort_unstable_by_key(|&id| segments[id].0);
+
+        let mut tree = Self {
+            segments,
+            by_start,
+            nodes: Vec::new(),
+            root: None,
+        };
+        tree.root = tree.build((0..tree.segments.len()).collect());
+        tree
+    }
+}
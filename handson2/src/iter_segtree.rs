@@ -0,0 +1,505 @@
+//! Iterative, bottom-up backend for the generic lazy segment tree.
+//!
+//! [`IterLazySegTree`] has the same semantics as
+//! [`LazySegTree`](crate::lazy_segtree::LazySegTree) but stores the tree in a
+//! power-of-two array of `2 * size` nodes (leaves at `size..size + n`) and
+//! walks it with loops instead of recursion. Only actions that fail on a node
+//! (Segment Tree Beats) fall back to an explicit-stack descent.
+//!
+//! Structures generic over a [`TreeBackend`] run on it with [`Iterative`],
+//! e.g. `problem1::SegmentTree<i64, Iterative>`.
+
+use std::fmt;
+use std::ops::Range;
+
+use crate::journal::{Checkpoint, Journal};
+use crate::lazy_segtree::{Action, LazyTree, Monoid, TreeBackend};
+
+/// Non-recursive lazy segment tree over a power-of-two number of leaves.
+///
+/// All ranges are 0-based and half-open (`start..end`). Queries take
+/// `&mut self` because they push pending tags down the tree. Updates can be
+/// undone with [`checkpoint`](Self::checkpoint) and
+/// [`rollback`](Self::rollback).
+///
+/// # Panics
+/// Every range method panics if `start > end` or `end > len()`.
+pub struct IterLazySegTree<M: Monoid, A: Action<M>> {
+    n: usize,
+    size: usize,
+    log: u32,
+    tree: Vec<M::Value>,
+    lazy: Vec<A::Tag>,
+    journal: Journal<(M::Value, Option<A::Tag>)>,
+}
+
+impl<M: Monoid, A: Action<M>> IterLazySegTree<M, A> {
+    /// Builds the tree over a copy of `values` in O(n).
+    pub fn new(values: &[M::Value]) -> Self {
+        let n = values.len();
+        let size = n.next_power_of_two();
+        let mut tree = vec![M::identity(); 2 * size];
+        tree[size..size + n].clone_from_slice(values);
+
+        let mut st = Self {
+            n,
+            size,
+            log: size.trailing_zeros(),
+            tree,
+            lazy: vec![A::identity(); size],
+            journal: Journal::new(),
+        };
+        for index in (1..size).rev() {
+            st.pull(index);
+        }
+        st
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    /// Returns `true` if the tree holds no elements.
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// Applies `tag` to every element of `range`.
+    pub fn apply(&mut self, range: Range<usize>, tag: &A::Tag) {
+        let Some((mut left, mut right)) = self.bounds(range) else {
+            return;
+        };
+        self.push_boundaries(left, right);
+
+        // Tag the canonical nodes covering [left, right).
+        let (start, end) = (left, right);
+        while left < right {
+            if left & 1 == 1 {
+                self.apply_node(left, tag);
+                left += 1;
+            }
+            if right & 1 == 1 {
+                right -= 1;
+                self.apply_node(right, tag);
+            }
+            left >>= 1;
+            right >>= 1;
+        }
+
+        // Recompute the ancestors of the two boundaries.
+        for level in 1..=self.log {
+            if (start >> level) << level != start {
+                self.pull(start >> level);
+            }
+            if (end >> level) << level != end {
+                self.pull((end - 1) >> level);
+            }
+        }
+    }
+
+    /// Returns the aggregate of `range` (the identity if it is empty).
+    pub fn query(&mut self, range: Range<usize>) -> M::Value {
+        let Some((mut left, mut right)) = self.bounds(range) else {
+            return M::identity();
+        };
+        self.push_boundaries(left, right);
+
+        let mut value_left = M::identity();
+        let mut value_right = M::identity();
+        while left < right {
+            if left & 1 == 1 {
+                value_left = M::combine(&value_left, &self.tree[left]);
+                left += 1;
+            }
+            if right & 1 == 1 {
+                right -= 1;
+                value_right = M::combine(&self.tree[right], &value_right);
+            }
+            left >>= 1;
+            right >>= 1;
+        }
+        M::combine(&value_left, &value_right)
+    }
+
+    /// Returns the leftmost index in `range` whose element satisfies `pred`.
+    ///
+    /// `pred` is also used to prune whole nodes, so it must hold for the
+    /// aggregate of every node containing an element that satisfies it.
+    pub fn find_first<P>(&mut self, range: Range<usize>, pred: P) -> Option<usize>
+    where
+        P: Fn(&M::Value) -> bool,
+    {
+        let (mut left, mut right) = self.bounds(range)?;
+        self.push_boundaries(left, right);
+
+        // Left canonical nodes come in left-to-right order; right ones in
+        // reverse, so they are buffered (at most one per level).
+        let mut nodes_right = [0; usize::BITS as usize];
+        let mut count = 0;
+        while left < right {
+            if left & 1 == 1 {
+                if let Some(found) = self.search_subtree(left, &pred) {
+                    return Some(found);
+                }
+                left += 1;
+            }
+            if right & 1 == 1 {
+                right -= 1;
+                nodes_right[count] = right;
+                count += 1;
+            }
+            left >>= 1;
+            right >>= 1;
+        }
+
+        nodes_right[..count]
+            .iter()
+            .rev()
+            .find_map(|&root| self.search_subtree(root, &pred))
+    }
+
+    /// Returns the largest `end` such that `pred(query(start..end))` holds.
+    ///
+    /// `pred` must be monotone (true on a range implies true on every
+    /// shorter range with the same start) and hold for the identity.
+    ///
+    /// # Panics
+    /// Panics if `start > len()`.
+    pub fn max_right<P>(&mut self, start: usize, pred: P) -> usize
+    where
+        P: Fn(&M::Value) -> bool,
+    {
+        assert!(
+            start <= self.n,
+            "Start {start} is out of bounds for length {}",
+            self.n
+        );
+        debug_assert!(pred(&M::identity()), "Predicate must hold for the identity");
+        if start == self.n {
+            return self.n;
+        }
+
+        let mut index = start + self.size;
+        for level in (1..=self.log).rev() {
+            self.push_down(index >> level);
+        }
+
+        let mut acc = M::identity();
+        loop {
+            // Climb while `index` is a left child: its parent starts at the same position.
+            while index & 1 == 0 {
+                index >>= 1;
+            }
+            let combined = M::combine(&acc, &self.tree[index]);
+            if !pred(&combined) {
+                // Descend to the first leaf where the predicate fails.
+                while index < self.size {
+                    self.push_down(index);
+                    index *= 2;
+                    let combined = M::combine(&acc, &self.tree[index]);
+                    if pred(&combined) {
+                        acc = combined;
+                        index += 1;
+                    }
+                }
+                return index - self.size;
+            }
+            acc = combined;
+            index += 1;
+            if index.is_power_of_two() {
+                return self.n;
+            }
+        }
+    }
+
+    /// Returns the smallest `start` such that `pred(query(start..end))` holds.
+    ///
+    /// `pred` must be monotone (true on a range implies true on every
+    /// shorter range with the same end) and hold for the identity.
+    ///
+    /// # Panics
+    /// Panics if `end > len()`.
+    pub fn min_left<P>(&mut self, end: usize, pred: P) -> usize
+    where
+        P: Fn(&M::Value) -> bool,
+    {
+        assert!(
+            end <= self.n,
+            "End {end} is out of bounds for length {}",
+            self.n
+        );
+        debug_assert!(pred(&M::identity()), "Predicate must hold for the identity");
+        if end == 0 {
+            return 0;
+        }
+
+        let mut index = end + self.size;
+        for level in (1..=self.log).rev() {
+            self.push_down((index - 1) >> level);
+        }
+
+        let mut acc = M::identity();
+        loop {
+            index -= 1;
+            // Climb while `index` is a right child: its parent ends at the same position.
+            while index > 1 && index & 1 == 1 {
+                index >>= 1;
+            }
+            let combined = M::combine(&self.tree[index], &acc);
+            if !pred(&combined) {
+                // Descend to the last leaf where the predicate fails.
+                while index < self.size {
+                    self.push_down(index);
+                    index = 2 * index + 1;
+                    let combined = M::combine(&self.tree[index], &acc);
+                    if pred(&combined) {
+                        acc = combined;
+                        index -= 1;
+                    }
+                }
+                return index + 1 - self.size;
+            }
+            acc = combined;
+            if index.is_power_of_two() {
+                return 0;
+            }
+        }
+    }
+
+    /// Returns the current values of all elements.
+    pub fn to_vec(&mut self) -> Vec<M::Value> {
+        for index in 1..self.size {
+            self.push_down(index);
+        }
+        self.tree[self.size..self.size + self.n].to_vec()
+    }
+
+    /// Opens a checkpoint at the current state; see [`journal`](crate::journal).
+    pub fn checkpoint(&mut self) -> Checkpoint {
+        self.journal.checkpoint()
+    }
+
+    /// Restores the state at `checkpoint`; see [`journal`](crate::journal).
+    ///
+    /// # Panics
+    /// Panics if `checkpoint` was already released.
+    pub fn rollback(&mut self, checkpoint: Checkpoint) {
+        self.journal.rollback(checkpoint, |index, (value, tag)| {
+            self.tree[index] = value;
+            if let Some(tag) = tag {
+                self.lazy[index] = tag;
+            }
+        });
+    }
+
+    /// Keeps the changes made since `checkpoint`; see [`journal`](crate::journal).
+    ///
+    /// # Panics
+    /// Panics if `checkpoint` was already released.
+    pub fn commit(&mut self, checkpoint: Checkpoint) {
+        self.journal.commit(checkpoint);
+    }
+
+    /// Validates a half-open range and converts it to leaf indices,
+    /// or `None` if it is empty.
+    fn bounds(&self, range: Range<usize>) -> Option<(usize, usize)> {
+        crate::check_range(&range, self.n);
+        (!range.is_empty()).then(|| (range.start + self.size, range.end + self.size))
+    }
+
+    /// Depth-first search for the leftmost leaf under `root` satisfying
+    /// `pred`, pruning with `pred` and visiting the left child first.
+    fn search_subtree<P>(&mut self, root: usize, pred: &P) -> Option<usize>
+    where
+        P: Fn(&M::Value) -> bool,
+    {
+        // At most one pending sibling per level, plus the current node.
+        let mut stack = [0; 2 * usize::BITS as usize];
+        stack[0] = root;
+        let mut top = 1;
+        while top > 0 {
+            top -= 1;
+            let index = stack[top];
+            if !pred(&self.tree[index]) {
+                continue;
+            }
+            if index >= self.size {
+                return Some(index - self.size);
+            }
+            self.push_down(index);
+            stack[top] = 2 * index + 1;
+            stack[top + 1] = 2 * index;
+            top += 2;
+        }
+        None
+    }
+
+    /// Pushes pending tags on the paths from the root to the boundaries of
+    /// the leaf range `[left, right)`.
+    fn push_boundaries(&mut self, left: usize, right: usize) {
+        for level in (1..=self.log).rev() {
+            if (left >> level) << level != left {
+                self.push_down(left >> level);
+            }
+            if (right >> level) << level != right {
+                self.push_down((right - 1) >> level);
+            }
+        }
+    }
+
+    /// Records node `index` on the journal before it is overwritten (leaves
+    /// have no tag).
+    fn save(&mut self, index: usize) {
+        if self.journal.is_recording() {
+            let tag = (index < self.size).then(|| self.lazy[index].clone());
+            let old = (self.tree[index].clone(), tag);
+            self.journal.record(index, old);
+        }
+    }
+
+    /// Recomputes a node from its children.
+    fn pull(&mut self, index: usize) {
+        self.save(index);
+        self.tree[index] = M::combine(&self.tree[2 * index], &self.tree[2 * index + 1]);
+    }
+
+    /// Push the node's pending tag down to its children.
+    fn push_down(&mut self, index: usize) {
+        if self.lazy[index] == A::identity() {
+            return;
+        }
+
+        self.save(index);
+        let tag = std::mem::replace(&mut self.lazy[index], A::identity());
+        for child in [2 * index, 2 * index + 1] {
+            self.save(child);
+            self.tree[child] = A::apply(&tag, &self.tree[child])
+                .expect("Action failed while pushing down a tag that succeeded on the parent");
+            if child < self.size {
+                self.lazy[child] = A::compose(&tag, &self.lazy[child]);
+            }
+        }
+    }
+
+    /// Applies `tag` to the whole subtree of `index`, descending with an
+    /// explicit stack wherever the action fails.
+    fn apply_node(&mut self, index: usize, tag: &A::Tag) {
+        if self.try_apply(index, tag) {
+            return;
+        }
+
+        // Post-order traversal: (node, children_done).
+        let mut stack = vec![(index, false)];
+        while let Some((index, children_done)) = stack.pop() {
+            if children_done {
+                self.pull(index);
+            } else if !self.try_apply(index, tag) {
+                assert!(index < self.size, "Action failed on a single element");
+                self.push_down(index);
+                stack.extend([(index, true), (2 * index + 1, false), (2 * index, false)]);
+            }
+        }
+    }
+
+    /// Tags a single node; returns `false` if the action fails on it.
+    fn try_apply(&mut self, index: usize, tag: &A::Tag) -> bool {
+        let Some(value) = A::apply(tag, &self.tree[index]) else {
+            return false;
+        };
+        self.save(index);
+        self.tree[index] = value;
+        if index < self.size {
+            self.lazy[index] = A::compose(tag, &self.lazy[index]);
+        }
+        true
+    }
+}
+
+impl<M: Monoid, A: Action<M>> Clone for IterLazySegTree<M, A> {
+    fn clone(&self) -> Self {
+        Self {
+            n: self.n,
+            size: self.size,
+            log: self.log,
+            tree: self.tree.clone(),
+            lazy: self.lazy.clone(),
+            journal: self.journal.clone(),
+        }
+    }
+}
+
+impl<M: Monoid, A: Action<M>> fmt::Debug for IterLazySegTree<M, A>
+where
+    M::Value: fmt::Debug,
+    A::Tag: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IterLazySegTree")
+            .field("n", &self.n)
+            .field("tree", &self.tree)
+            .field("lazy", &self.lazy)
+            .finish()
+    }
+}
+
+impl<M: Monoid, A: Action<M>> FromIterator<M::Value> for IterLazySegTree<M, A> {
+    fn from_iter<I: IntoIterator<Item = M::Value>>(iter: I) -> Self {
+        let values: Vec<M::Value> = iter.into_iter().collect();
+        Self::new(&values)
+    }
+}
+
+impl<M: Monoid, A: Action<M>> LazyTree<M, A> for IterLazySegTree<M, A> {
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn apply(&mut self, range: Range<usize>, tag: &A::Tag) {
+        self.apply(range, tag);
+    }
+
+    fn query(&mut self, range: Range<usize>) -> M::Value {
+        self.query(range)
+    }
+
+    fn find_first<P: Fn(&M::Value) -> bool>(
+        &mut self,
+        range: Range<usize>,
+        pred: P,
+    ) -> Option<usize> {
+        self.find_first(range, pred)
+    }
+
+    fn max_right<P: Fn(&M::Value) -> bool>(&mut self, start: usize, pred: P) -> usize {
+        self.max_right(start, pred)
+    }
+
+    fn min_left<P: Fn(&M::Value) -> bool>(&mut self, end: usize, pred: P) -> usize {
+        self.min_left(end, pred)
+    }
+
+    fn to_vec(&mut self) -> Vec<M::Value> {
+        self.to_vec()
+    }
+
+    fn checkpoint(&mut self) -> Checkpoint {
+        self.checkpoint()
+    }
+
+    fn rollback(&mut self, checkpoint: Checkpoint) {
+        self.rollback(checkpoint);
+    }
+
+    fn commit(&mut self, checkpoint: Checkpoint) {
+        self.commit(checkpoint);
+    }
+}
+
+/// [`TreeBackend`] of the iterative [`IterLazySegTree`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Iterative;
+
+impl TreeBackend for Iterative {
+    type Tree<M: Monoid, A: Action<M>> = IterLazySegTree<M, A>;
+}
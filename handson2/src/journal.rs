@@ -0,0 +1,100 @@
+//! Undo journal shared by the segment trees.
+//!
+//! The lazy, iterative and historic-max trees (and the Problem 1 tree built
+//! on the lazy one) expose the same three methods:
+//!
+//! - `checkpoint()` opens a [`Checkpoint`] at the current state. Until it is
+//!   released, every node overwritten by an update, or by a query pushing
+//!   tags down, is recorded on the journal.
+//! - `rollback(checkpoint)` restores the state at `checkpoint` by replaying
+//!   the journal in reverse, so it costs time proportional to the number of
+//!   nodes changed since then rather than to the size of the tree.
+//! - `commit(checkpoint)` keeps the changes made since `checkpoint`. Once no
+//!   checkpoint is open the journal is cleared and updates stop recording.
+//!
+//! Both `rollback` and `commit` release the checkpoint together with every
+//! checkpoint taken after it, and panic if it was already released. Without
+//! an open checkpoint the trees pay only a branch per overwritten node.
+
+/// Token returned by `checkpoint()` on a segment tree.
+///
+/// Checkpoints nest like a stack: rolling back or committing a checkpoint
+/// also releases every checkpoint taken after it.
+#[derive(Debug, PartialEq, Eq)]
+#[must_use = "a checkpoint must be passed to `rollback` or `commit`"]
+pub struct Checkpoint {
+    id: u64,
+    depth: usize,
+}
+
+/// Journal of `(slot index, previous content)` entries.
+#[derive(Clone, Debug)]
+pub(crate) struct Journal<T> {
+    entries: Vec<(usize, T)>,
+    /// Open checkpoints as `(id, journal length when taken)`.
+    marks: Vec<(u64, usize)>,
+    next_id: u64,
+}
+
+impl<T> Journal<T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            marks: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Returns `true` if overwritten slots must be recorded.
+    pub(crate) fn is_recording(&self) -> bool {
+        !self.marks.is_empty()
+    }
+
+    /// Records the previous content of slot `index`.
+    pub(crate) fn record(&mut self, index: usize, old: T) {
+        self.entries.push((index, old));
+    }
+
+    /// Opens a new checkpoint at the current state.
+    pub(crate) fn checkpoint(&mut self) -> Checkpoint {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.marks.push((id, self.entries.len()));
+        Checkpoint {
+            id,
+            depth: self.marks.len() - 1,
+        }
+    }
+
+    /// Undoes every change recorded since `checkpoint`, handing each slot and
+    /// its previous content to `restore` (most recent first).
+    pub(crate) fn rollback<F>(&mut self, checkpoint: Checkpoint, mut restore: F)
+    where
+        F: FnMut(usize, T),
+    {
+        let len = self.release(checkpoint);
+        while self.entries.len() > len {
+            let (index, old) = self.entries.pop().expect("Journal entry exists");
+            restore(index, old);
+        }
+    }
+
+    /// Keeps every change made since `checkpoint` and closes it.
+    pub(crate) fn commit(&mut self, checkpoint: Checkpoint) {
+        self.release(checkpoint);
+        if self.marks.is_empty() {
+            self.entries.clear();
+        }
+    }
+
+    /// Closes `checkpoint` and the ones nested in it, returning the journal
+    /// length at the time it was taken.
+    fn release(&mut self, checkpoint: Checkpoint) -> usize {
+        let Some(&(id, len)) = self.marks.get(checkpoint.depth) else {
+            panic!("Checkpoint was already released");
+        };
+        assert!(id == checkpoint.id, "Checkpoint was already released");
+        self.marks.truncate(checkpoint.depth);
+        len
+    }
+}
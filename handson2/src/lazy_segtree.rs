@@ -0,0 +1,818 @@
+//! Generic lazy segment tree shared by the HandsOn 2 problems.
+//!
+//! The tree is parameterized by a [`Monoid`] describing the aggregate stored
+//! in each node and by an [`Action`] describing the lazy updates applied to
+//! ranges. Actions may *fail* on a node whose aggregate does not carry enough
+//! information (Segment Tree Beats): the tree then pushes pending tags down
+//! and retries on the children.
+//!
+//! Ready-made monoids: [`Sum`], [`Min`], [`Max`] and [`MinMax`], all
+//! updatable with the [`Add`] action. They are generic over the [`Element`]
+//! integer types and default to `i64`.
+//!
+//! [`LazyTree`] is the interface shared with the iterative
+//! [`IterLazySegTree`](crate::iter_segtree::IterLazySegTree); code built on
+//! top of a tree picks one through a [`TreeBackend`] marker ([`Recursive`]
+//! or [`Iterative`](crate::iter_segtree::Iterative)).
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{self, Range};
+
+use crate::journal::{Checkpoint, Journal};
+
+/// An associative aggregate with an identity element.
+pub trait Monoid {
+    type Value: Clone;
+
+    /// The identity element: `combine(identity(), x) == x`.
+    fn identity() -> Self::Value;
+
+    /// Combines the aggregates of two adjacent ranges (`left` before `right`).
+    fn combine(left: &Self::Value, right: &Self::Value) -> Self::Value;
+}
+
+/// A lazy update that can be applied to the aggregates of a [`Monoid`].
+pub trait Action<M: Monoid> {
+    type Tag: Clone + PartialEq;
+
+    /// The tag that leaves every value unchanged.
+    fn identity() -> Self::Tag;
+
+    /// Returns the tag equivalent to applying `inner` first and then `outer`.
+    fn compose(outer: &Self::Tag, inner: &Self::Tag) -> Self::Tag;
+
+    /// Applies `tag` to the aggregate `value` of a whole node.
+    ///
+    /// Returns `None` if the result cannot be computed from the aggregate
+    /// alone; the tree then descends into the children. Applying a tag to a
+    /// single element, or pushing down a tag that succeeded on the parent,
+    /// must never fail.
+    fn apply(tag: &Self::Tag, value: &M::Value) -> Option<M::Value>;
+}
+
+/// Operations of a lazy segment tree over `M` updated by `A`, implemented by
+/// [`LazySegTree`] and [`IterLazySegTree`](crate::iter_segtree::IterLazySegTree)
+/// with identical semantics. See [`LazySegTree`] for each method.
+pub trait LazyTree<M: Monoid, A: Action<M>>: Clone + FromIterator<M::Value> {
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn apply(&mut self, range: Range<usize>, tag: &A::Tag);
+
+    fn query(&mut self, range: Range<usize>) -> M::Value;
+
+    fn find_first<P: Fn(&M::Value) -> bool>(
+        &mut self,
+        range: Range<usize>,
+        pred: P,
+    ) -> Option<usize>;
+
+    fn max_right<P: Fn(&M::Value) -> bool>(&mut self, start: usize, pred: P) -> usize;
+
+    fn min_left<P: Fn(&M::Value) -> bool>(&mut self, end: usize, pred: P) -> usize;
+
+    fn to_vec(&mut self) -> Vec<M::Value>;
+
+    fn checkpoint(&mut self) -> Checkpoint;
+
+    fn rollback(&mut self, checkpoint: Checkpoint);
+
+    fn commit(&mut self, checkpoint: Checkpoint);
+}
+
+/// Selects a [`LazyTree`] implementation for structures generic over it,
+/// such as [`SegmentTree`](crate::problem1::SegmentTree).
+pub trait TreeBackend {
+    type Tree<M: Monoid, A: Action<M>>: LazyTree<M, A>;
+}
+
+/// [`TreeBackend`] of the recursive [`LazySegTree`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Recursive;
+
+impl TreeBackend for Recursive {
+    type Tree<M: Monoid, A: Action<M>> = LazySegTree<M, A>;
+}
+
+/// Recursive lazy segment tree over `4 * n` nodes, rooted at index `1`.
+///
+/// All ranges are 0-based and half-open (`start..end`). Queries take
+/// `&mut self` because they push pending tags down the tree.
+///
+/// Updates can be undone with [`checkpoint`](Self::checkpoint) and
+/// [`rollback`](Self::rollback).
+///
+/// # Panics
+/// Every range method panics if `start > end` or `end > len()`.
+pub struct LazySegTree<M: Monoid, A: Action<M>> {
+    n: usize,
+    tree: Vec<M::Value>,
+    lazy: Vec<A::Tag>,
+    journal: Journal<(M::Value, A::Tag)>,
+}
+
+impl<M: Monoid, A: Action<M>> LazySegTree<M, A> {
+    /// Builds the tree over a copy of `values` in O(n).
+    pub fn new(values: &[M::Value]) -> Self {
+        let n = values.len();
+        let size = 4 * n.max(1);
+        let mut st = Self {
+            n,
+            tree: vec![M::identity(); size],
+            lazy: vec![A::identity(); size],
+            journal: Journal::new(),
+        };
+        if n > 0 {
+            st.build(1, 0, n - 1, values);
+        }
+        st
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    /// Returns `true` if the tree holds no elements.
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// Applies `tag` to every element of `range`.
+    pub fn apply(&mut self, range: Range<usize>, tag: &A::Tag) {
+        if let Some((ql, qr)) = self.bounds(range) {
+            self.apply_rec(1, 0, self.n - 1, ql, qr, tag);
+        }
+    }
+
+    /// Returns the aggregate of `range` (the identity if it is empty).
+    pub fn query(&mut self, range: Range<usize>) -> M::Value {
+        match self.bounds(range) {
+            Some((ql, qr)) => self.query_rec(1, 0, self.n - 1, ql, qr),
+            None => M::identity(),
+        }
+    }
+
+    /// Returns the leftmost index in `range` whose element satisfies `pred`.
+    ///
+    /// `pred` is also used to prune whole nodes, so it must hold for the
+    /// aggregate of every node containing an element that satisfies it.
+    pub fn find_first<P>(&mut self, range: Range<usize>, pred: P) -> Option<usize>
+    where
+        P: Fn(&M::Value) -> bool,
+    {
+        let (ql, qr) = self.bounds(range)?;
+        self.find_first_rec(1, 0, self.n - 1, ql, qr, &pred)
+    }
+
+    /// Returns the largest `end` such that `pred(query(start..end))` holds.
+    ///
+    /// `pred` must be monotone (true on a range implies true on every
+    /// shorter range with the same start) and hold for the identity.
+    /// Runs in O(log n).
+    ///
+    /// # Panics
+    /// Panics if `start > len()`.
+    pub fn max_right<P>(&mut self, start: usize, pred: P) -> usize
+    where
+        P: Fn(&M::Value) -> bool,
+    {
+        assert!(
+            start <= self.n,
+            "Start {start} is out of bounds for length {}",
+            self.n
+        );
+        debug_assert!(pred(&M::identity()), "Predicate must hold for the identity");
+        if start == self.n {
+            return self.n;
+        }
+        let mut acc = M::identity();
+        self.max_right_rec(1, 0, self.n - 1, start, &pred, &mut acc)
+            .unwrap_or(self.n)
+    }
+
+    /// Returns the smallest `start` such that `pred(query(start..end))` holds.
+    ///
+    /// `pred` must be monotone (true on a range implies true on every
+    /// shorter range with the same end) and hold for the identity.
+    /// Runs in O(log n).
+    ///
+    /// # Panics
+    /// Panics if `end > len()`.
+    pub fn min_left<P>(&mut self, end: usize, pred: P) -> usize
+    where
+        P: Fn(&M::Value) -> bool,
+    {
+        assert!(
+            end <= self.n,
+            "End {end} is out of bounds for length {}",
+            self.n
+        );
+        debug_assert!(pred(&M::identity()), "Predicate must hold for the identity");
+        if end == 0 {
+            return 0;
+        }
+        let mut acc = M::identity();
+        self.min_left_rec(1, 0, self.n - 1, end, &pred, &mut acc)
+            .unwrap_or(0)
+    }
+
+    /// Returns the current values of all elements.
+    pub fn to_vec(&mut self) -> Vec<M::Value> {
+        let mut values = Vec::with_capacity(self.n);
+        if self.n > 0 {
+            self.collect_rec(1, 0, self.n - 1, &mut values);
+        }
+        values
+    }
+
+    /// Opens a checkpoint at the current state; see [`journal`](crate::journal).
+    pub fn checkpoint(&mut self) -> Checkpoint {
+        self.journal.checkpoint()
+    }
+
+    /// Restores the state at `checkpoint`; see [`journal`](crate::journal).
+    ///
+    /// # Panics
+    /// Panics if `checkpoint` was already released.
+    pub fn rollback(&mut self, checkpoint: Checkpoint) {
+        self.journal.rollback(checkpoint, |index, (value, tag)| {
+            self.tree[index] = value;
+            self.lazy[index] = tag;
+        });
+    }
+
+    /// Keeps the changes made since `checkpoint`; see [`journal`](crate::journal).
+    ///
+    /// # Panics
+    /// Panics if `checkpoint` was already released.
+    pub fn commit(&mut self, checkpoint: Checkpoint) {
+        self.journal.commit(checkpoint);
+    }
+
+    /// Validates a half-open range and converts it to inclusive bounds,
+    /// or `None` if it is empty.
+    fn bounds(&self, range: Range<usize>) -> Option<(usize, usize)> {
+        crate::check_range(&range, self.n);
+        (!range.is_empty()).then(|| (range.start, range.end - 1))
+    }
+
+    fn build(&mut self, index: usize, left: usize, right: usize, values: &[M::Value]) {
+        // Leaf node
+        if left == right {
+            self.tree[index] = values[left].clone();
+            return;
+        }
+
+        let mid = (left + right) / 2;
+        let left_child = index * 2;
+        let right_child = left_child + 1;
+
+        self.build(left_child, left, mid, values);
+        self.build(right_child, mid + 1, right, values);
+
+        self.pull(index);
+    }
+
+    /// Records node `index` on the journal before it is overwritten.
+    fn save(&mut self, index: usize) {
+        if self.journal.is_recording() {
+            let old = (self.tree[index].clone(), self.lazy[index].clone());
+            self.journal.record(index, old);
+        }
+    }
+
+    /// Recomputes a node from its children.
+    fn pull(&mut self, index: usize) {
+        self.save(index);
+        self.tree[index] = M::combine(&self.tree[index * 2], &self.tree[index * 2 + 1]);
+    }
+
+    /// Push the node's pending tag down to its children.
+    fn push_down(&mut self, index: usize) {
+        if self.lazy[index] == A::identity() {
+            return;
+        }
+
+        self.save(index);
+        let tag = std::mem::replace(&mut self.lazy[index], A::identity());
+        for child in [index * 2, index * 2 + 1] {
+            self.save(child);
+            self.tree[child] = A::apply(&tag, &self.tree[child])
+                .expect("Action failed while pushing down a tag that succeeded on the parent");
+            self.lazy[child] = A::compose(&tag, &self.lazy[child]);
+        }
+    }
+
+    fn apply_rec(
+        &mut self,
+        index: usize,
+        left: usize,
+        right: usize,
+        ql: usize,
+        qr: usize,
+        tag: &A::Tag,
+    ) {
+        // No overlap
+        if right < ql || qr < left {
+            return;
+        }
+
+        // Fully covered: try to tag the node directly
+        if ql <= left && right <= qr {
+            if let Some(value) = A::apply(tag, &self.tree[index]) {
+                self.save(index);
+                self.tree[index] = value;
+                if left != right {
+                    self.lazy[index] = A::compose(tag, &self.lazy[index]);
+                }
+                return;
+            }
+            assert!(left != right, "Action failed on a single element");
+        }
+
+        // Partial overlap or failed tag: push down and recurse
+        self.push_down(index);
+        let mid = (left + right) / 2;
+        self.apply_rec(index * 2, left, mid, ql, qr, tag);
+        self.apply_rec(index * 2 + 1, mid + 1, right, ql, qr, tag);
+        self.pull(index);
+    }
+
+    fn query_rec(
+        &mut self,
+        index: usize,
+        left: usize,
+        right: usize,
+        ql: usize,
+        qr: usize,
+    ) -> M::Value {
+        // No overlap
+        if right < ql || qr < left {
+            return M::identity();
+        }
+        // Fully covered
+        if ql <= left && right <= qr {
+            return self.tree[index].clone();
+        }
+
+        // Partial overlap; push down and recurse
+        self.push_down(index);
+        let mid = (left + right) / 2;
+        let value_left = self.query_rec(index * 2, left, mid, ql, qr);
+        let value_right = self.query_rec(index * 2 + 1, mid + 1, right, ql, qr);
+        M::combine(&value_left, &value_right)
+    }
+
+    fn find_first_rec<P>(
+        &mut self,
+        index: usize,
+        left: usize,
+        right: usize,
+        ql: usize,
+        qr: usize,
+        pred: &P,
+    ) -> Option<usize>
+    where
+        P: Fn(&M::Value) -> bool,
+    {
+        // No overlap, or no element of this node can satisfy the predicate
+        if right < ql || qr < left || !pred(&self.tree[index]) {
+            return None;
+        }
+
+        // Leaf node: single position
+        if left == right {
+            return Some(left);
+        }
+
+        // Check left child first; if found, no need to check right.
+        self.push_down(index);
+        let mid = (left + right) / 2;
+        self.find_first_rec(index * 2, left, mid, ql, qr, pred)
+            .or_else(|| self.find_first_rec(index * 2 + 1, mid + 1, right, ql, qr, pred))
+    }
+
+    /// Extends `acc` with the elements of this node from `start` on; returns
+    /// the first index where `pred` stops holding, if inside this node.
+    fn max_right_rec<P>(
+        &mut self,
+        index: usize,
+        left: usize,
+        right: usize,
+        start: usize,
+        pred: &P,
+        acc: &mut M::Value,
+    ) -> Option<usize>
+    where
+        P: Fn(&M::Value) -> bool,
+    {
+        // Entirely before the start
+        if right < start {
+            return None;
+        }
+
+        // Fully inside: take the whole node if the predicate still holds
+        if start <= left {
+            let combined = M::combine(acc, &self.tree[index]);
+            if pred(&combined) {
+                *acc = combined;
+                return None;
+            }
+            if left == right {
+                return Some(left);
+            }
+        }
+
+        self.push_down(index);
+        let mid = (left + right) / 2;
+        self.max_right_rec(index * 2, left, mid, start, pred, acc)
+            .or_else(|| self.max_right_rec(index * 2 + 1, mid + 1, right, start, pred, acc))
+    }
+
+    /// Extends `acc` leftwards with the elements of this node before `end`;
+    /// returns the index right after the element where `pred` stops holding.
+    fn min_left_rec<P>(
+        &mut self,
+        index: usize,
+        left: usize,
+        right: usize,
+        end: usize,
+        pred: &P,
+        acc: &mut M::Value,
+    ) -> Option<usize>
+    where
+        P: Fn(&M::Value) -> bool,
+    {
+        // Entirely after the end
+        if left >= end {
+            return None;
+        }
+
+        // Fully inside: take the whole node if the predicate still holds
+        if right < end {
+            let combined = M::combine(&self.tree[index], acc);
+            if pred(&combined) {
+                *acc = combined;
+                return None;
+            }
+            if left == right {
+                return Some(left + 1);
+            }
+        }
+
+        self.push_down(index);
+        let mid = (left + right) / 2;
+        self.min_left_rec(index * 2 + 1, mid + 1, right, end, pred, acc)
+            .or_else(|| self.min_left_rec(index * 2, left, mid, end, pred, acc))
+    }
+
+    fn collect_rec(&mut self, index: usize, left: usize, right: usize, values: &mut Vec<M::Value>) {
+        if left == right {
+            values.push(self.tree[index].clone());
+            return;
+        }
+
+        self.push_down(index);
+        let mid = (left + right) / 2;
+        self.collect_rec(index * 2, left, mid, values);
+        self.collect_rec(index * 2 + 1, mid + 1, right, values);
+    }
+}
+
+impl<M: Monoid, A: Action<M>> Clone for LazySegTree<M, A> {
+    fn clone(&self) -> Self {
+        Self {
+            n: self.n,
+            tree: self.tree.clone(),
+            lazy: self.lazy.clone(),
+            journal: self.journal.clone(),
+        }
+    }
+}
+
+impl<M: Monoid, A: Action<M>> fmt::Debug for LazySegTree<M, A>
+where
+    M::Value: fmt::Debug,
+    A::Tag: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazySegTree")
+            .field("n", &self.n)
+            .field("tree", &self.tree)
+            .field("lazy", &self.lazy)
+            .finish()
+    }
+}
+
+impl<M: Monoid, A: Action<M>> FromIterator<M::Value> for LazySegTree<M, A> {
+    fn from_iter<I: IntoIterator<Item = M::Value>>(iter: I) -> Self {
+        let values: Vec<M::Value> = iter.into_iter().collect();
+        Self::new(&values)
+    }
+}
+
+impl<M: Monoid, A: Action<M>> LazyTree<M, A> for LazySegTree<M, A> {
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn apply(&mut self, range: Range<usize>, tag: &A::Tag) {
+        self.apply(range, tag);
+    }
+
+    fn query(&mut self, range: Range<usize>) -> M::Value {
+        self.query(range)
+    }
+
+    fn find_first<P: Fn(&M::Value) -> bool>(
+        &mut self,
+        range: Range<usize>,
+        pred: P,
+    ) -> Option<usize> {
+        self.find_first(range, pred)
+    }
+
+    fn max_right<P: Fn(&M::Value) -> bool>(&mut self, start: usize, pred: P) -> usize {
+        self.max_right(start, pred)
+    }
+
+    fn min_left<P: Fn(&M::Value) -> bool>(&mut self, end: usize, pred: P) -> usize {
+        self.min_left(end, pred)
+    }
+
+    fn to_vec(&mut self) -> Vec<M::Value> {
+        self.to_vec()
+    }
+
+    fn checkpoint(&mut self) -> Checkpoint {
+        self.checkpoint()
+    }
+
+    fn rollback(&mut self, checkpoint: Checkpoint) {
+        self.rollback(checkpoint);
+    }
+
+    fn commit(&mut self, checkpoint: Checkpoint) {
+        self.commit(checkpoint);
+    }
+}
+
+/// Integer type stored by the ready-made monoids and by
+/// [`SegmentTree`](crate::problem1::SegmentTree).
+///
+/// `MIN` and `MAX` are only used as identities (`min(MAX, x) == x` holds for
+/// every `x`, `MAX` included), never as markers for missing values, so the
+/// whole range of the type is valid data. Sums are computed with wrapping
+/// arithmetic: they are exact whenever the true sum fits in the type, even if
+/// some partial sums do not.
+///
+/// # Examples
+/// ```
+/// use handson2::lazy_segtree::{Add, LazySegTree, Min, Sum, SumLen};
+///
+/// let mut mins: LazySegTree<Min<u64>, Add<u64>> = LazySegTree::new(&[u64::MAX, 3]);
+/// assert_eq!(mins.query(0..1), u64::MAX);
+///
+/// let mut sums: LazySegTree<Sum<i32>, Add<i32>> =
+///     [i32::MAX, i32::MAX, i32::MIN].into_iter().map(SumLen::from).collect();
+/// // The partial sum `i32::MAX + i32::MAX` overflows, the total does not
+/// assert_eq!(sums.query(0..3).sum, i32::MAX - 1);
+/// ```
+pub trait Element: Copy + Ord + fmt::Debug + ops::Add<Output = Self> {
+    const ZERO: Self;
+    const MIN: Self;
+    const MAX: Self;
+
+    /// Converts a length, wrapping around like the sums do.
+    fn from_len(len: usize) -> Self;
+
+    fn wrapping_add(self, other: Self) -> Self;
+    fn wrapping_sub(self, other: Self) -> Self;
+    fn wrapping_mul(self, other: Self) -> Self;
+    fn saturating_add(self, other: Self) -> Self;
+    fn overflowing_add(self, other: Self) -> (Self, bool);
+}
+
+macro_rules! impl_element {
+    ($($t:ty),*) => {$(
+        impl Element for $t {
+            const ZERO: Self = 0;
+            const MIN: Self = <$t>::MIN;
+            const MAX: Self = <$t>::MAX;
+
+            fn from_len(len: usize) -> Self {
+                len as $t
+            }
+
+            fn wrapping_add(self, other: Self) -> Self {
+                <$t>::wrapping_add(self, other)
+            }
+
+            fn wrapping_sub(self, other: Self) -> Self {
+                <$t>::wrapping_sub(self, other)
+            }
+
+            fn wrapping_mul(self, other: Self) -> Self {
+                <$t>::wrapping_mul(self, other)
+            }
+
+            fn saturating_add(self, other: Self) -> Self {
+                <$t>::saturating_add(self, other)
+            }
+
+            fn overflowing_add(self, other: Self) -> (Self, bool) {
+                <$t>::overflowing_add(self, other)
+            }
+        }
+    )*};
+}
+
+impl_element!(i32, i64, u64, i128);
+
+/// Sum monoid. Values carry their length so that [`Add`] can update them.
+pub struct Sum<T = i64>(PhantomData<T>);
+
+/// Aggregate of the [`Sum`] monoid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SumLen<T = i64> {
+    pub sum: T,
+    pub len: usize,
+}
+
+impl<T: Element> From<T> for SumLen<T> {
+    fn from(value: T) -> Self {
+        Self { sum: value, len: 1 }
+    }
+}
+
+impl<T: Element> Monoid for Sum<T> {
+    type Value = SumLen<T>;
+
+    fn identity() -> SumLen<T> {
+        SumLen {
+            sum: T::ZERO,
+            len: 0,
+        }
+    }
+
+    fn combine(left: &SumLen<T>, right: &SumLen<T>) -> SumLen<T> {
+        SumLen {
+            sum: left.sum.wrapping_add(right.sum),
+            len: left.len + right.len,
+        }
+    }
+}
+
+/// Minimum monoid, with `T::MAX` as identity.
+pub struct Min<T = i64>(PhantomData<T>);
+
+impl<T: Element> Monoid for Min<T> {
+    type Value = T;
+
+    fn identity() -> T {
+        T::MAX
+    }
+
+    fn combine(left: &T, right: &T) -> T {
+        *left.min(right)
+    }
+}
+
+/// Maximum monoid, with `T::MIN` as identity.
+pub struct Max<T = i64>(PhantomData<T>);
+
+impl<T: Element> Monoid for Max<T> {
+    type Value = T;
+
+    fn identity() -> T {
+        T::MIN
+    }
+
+    fn combine(left: &T, right: &T) -> T {
+        *left.max(right)
+    }
+}
+
+/// Minimum and maximum monoid.
+///
+/// The identity is `{ min: T::MAX, max: T::MIN }`, the only value with
+/// `min > max`, so an empty range is never mistaken for real data.
+pub struct MinMax<T = i64>(PhantomData<T>);
+
+/// Aggregate of the [`MinMax`] monoid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MinMaxValue<T = i64> {
+    pub min: T,
+    pub max: T,
+}
+
+impl<T: Element> From<T> for MinMaxValue<T> {
+    fn from(value: T) -> Self {
+        Self {
+            min: value,
+            max: value,
+        }
+    }
+}
+
+impl<T: Element> Monoid for MinMax<T> {
+    type Value = MinMaxValue<T>;
+
+    fn identity() -> MinMaxValue<T> {
+        MinMaxValue {
+            min: T::MAX,
+            max: T::MIN,
+        }
+    }
+
+    fn combine(left: &MinMaxValue<T>, right: &MinMaxValue<T>) -> MinMaxValue<T> {
+        MinMaxValue {
+            min: left.min.min(right.min),
+            max: left.max.max(right.max),
+        }
+    }
+}
+
+/// Range addition: the tag is the amount added to every element.
+///
+/// Tags compose and apply with wrapping arithmetic. Pending additions may
+/// cancel out (`MAX` then `1` on `MIN`), and the result is exact as long as
+/// every element stays within `T`.
+pub struct Add<T = i64>(PhantomData<T>);
+
+impl<T: Element> Action<Sum<T>> for Add<T> {
+    type Tag = T;
+
+    fn identity() -> T {
+        T::ZERO
+    }
+
+    fn compose(outer: &T, inner: &T) -> T {
+        outer.wrapping_add(*inner)
+    }
+
+    fn apply(tag: &T, value: &SumLen<T>) -> Option<SumLen<T>> {
+        Some(SumLen {
+            sum: value
+                .sum
+                .wrapping_add(tag.wrapping_mul(T::from_len(value.len))),
+            len: value.len,
+        })
+    }
+}
+
+impl<T: Element> Action<Min<T>> for Add<T> {
+    type Tag = T;
+
+    fn identity() -> T {
+        T::ZERO
+    }
+
+    fn compose(outer: &T, inner: &T) -> T {
+        outer.wrapping_add(*inner)
+    }
+
+    fn apply(tag: &T, value: &T) -> Option<T> {
+        Some(value.wrapping_add(*tag))
+    }
+}
+
+impl<T: Element> Action<Max<T>> for Add<T> {
+    type Tag = T;
+
+    fn identity() -> T {
+        T::ZERO
+    }
+
+    fn compose(outer: &T, inner: &T) -> T {
+        outer.wrapping_add(*inner)
+    }
+
+    fn apply(tag: &T, value: &T) -> Option<T> {
+        Some(value.wrapping_add(*tag))
+    }
+}
+
+impl<T: Element> Action<MinMax<T>> for Add<T> {
+    type Tag = T;
+
+    fn identity() -> T {
+        T::ZERO
+    }
+
+    fn compose(outer: &T, inner: &T) -> T {
+        outer.wrapping_add(*inner)
+    }
+
+    fn apply(tag: &T, value: &MinMaxValue<T>) -> Option<MinMaxValue<T>> {
+        Some(MinMaxValue {
+            min: value.min.wrapping_add(*tag),
+            max: value.max.wrapping_add(*tag),
+        })
+    }
+}
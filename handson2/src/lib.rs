@@ -1,2 +1,28 @@
+pub mod coverage_index;
+pub mod fenwick;
+pub mod historic_max;
+pub mod interval_tree;
+pub mod iter_segtree;
+pub mod journal;
+pub mod lazy_segtree;
+pub mod mo;
+pub mod persistent_segtree;
 pub mod problem1;
 pub mod problem2;
+pub mod problem2_2d;
+pub mod sparse_segtree;
+pub mod sparse_table;
+
+use std::ops::Range;
+
+/// Panics unless `range` is a half-open range within a sequence of length
+/// `len`.
+pub(crate) fn check_range(range: &Range<usize>, len: usize) {
+    assert!(
+        range.start <= range.end && range.end <= len,
+        "Range {}..{} is out of bounds for length {}",
+        range.start,
+        range.end,
+        len
+    );
+}
@@ -0,0 +1,176 @@
+//! Mo's algorithm: offline range queries answered by a sliding window.
+//!
+//! Some range questions (distinct values, frequency of the mode, "is some
+//! value equal to `k`") have no small mergeable aggregate, so they do not fit
+//! a segment tree. They do fit a window that can grow or shrink by one
+//! element at either end. Mo's algorithm sorts the queries so that the window
+//! moves O(n √q) steps in total when visiting all of them.
+
+use std::ops::Range;
+
+/// State of the window, updated one element at a time.
+///
+/// The window always covers a contiguous range of indices. `add` and
+/// `remove` are called with the index entering or leaving it; `answer` is
+/// called when the window is exactly the range of query number `query`.
+pub trait MoState {
+    type Answer;
+
+    /// Adds the element at `index` to the window.
+    fn add(&mut self, index: usize);
+
+    /// Removes the element at `index` from the window.
+    fn remove(&mut self, index: usize);
+
+    /// Returns the answer of query number `query` for the current window.
+    fn answer(&self, query: usize) -> Self::Answer;
+}
+
+/// Order in which [`process`] visits the queries.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Order {
+    /// Classic order: by blocks of `len / √q` start positions, then by end
+    /// (alternating direction between blocks).
+    Blocks,
+    /// Queries sorted along a Hilbert curve over the `(start, end)` plane,
+    /// which usually moves the window less than [`Order::Blocks`].
+    #[default]
+    Hilbert,
+}
+
+/// Answers `queries` (0-based half-open ranges of `0..len`) with Mo's
+/// algorithm and returns the answers in query order.
+///
+/// The window starts empty and every element enters and leaves it through
+/// `state`, with O(len · √q) calls of `add` / `remove` overall.
+///
+/// # Panics
+/// Panics if a query range has `start > end` or `end > len`.
+///
+/// # Examples
+/// ```
+/// use std::collections::HashMap;
+///
+/// use handson2::mo::{MoState, Order, process};
+///
+/// // Number of distinct values in each range
+/// struct Distinct<'a> {
+///     values: &'a [u32],
+///     counts: HashMap<u32, usize>,
+/// }
+///
+/// impl MoState for Distinct<'_> {
+///     type Answer = usize;
+///
+///     fn add(&mut self, index: usize) {
+///         *self.counts.entry(self.values[index]).or_default() += 1;
+///     }
+///
+///     fn remove(&mut self, index: usize) {
+///         let count = self.counts.get_mut(&self.values[index]).unwrap();
+///         *count -= 1;
+///         if *count == 0 {
+///             self.counts.remove(&self.values[index]);
+///         }
+///     }
+///
+///     fn answer(&self, _query: usize) -> usize {
+///         self.counts.len()
+///     }
+/// }
+///
+/// let values = [1, 2, 1, 3, 2];
+/// let mut state = Distinct { values: &values, counts: HashMap::new() };
+/// let answers = process(&mut state, values.len(), &[0..3, 1..5, 2..2], Order::Hilbert);
+/// assert_eq!(answers, vec![2, 3, 0]);
+/// ```
+pub fn process<S: MoState>(
+    state: &mut S,
+    len: usize,
+    queries: &[Range<usize>],
+    order: Order,
+) -> Vec<S::Answer> {
+    for range in queries {
+        crate::check_range(range, len);
+    }
+
+    let mut ids: Vec<usize> = (0..queries.len()).collect();
+    match order {
+        Order::Blocks => {
+            let block = (len / (queries.len().isqrt()).max(1)).max(1);
+            ids.sort_by_cached_key(|&id| {
+                let Range { start, end } = queries[id];
+                // Alternate the direction of `end` so the window does not
+                // jump back at every block change
+                let block_id = start / block;
+                let end = if block_id.is_multiple_of(2) {
+                    end
+                } else {
+                    len - end
+                };
+                (block_id, end)
+            });
+        }
+        Order::Hilbert => {
+            let side = (len as u64 + 1).next_power_of_two();
+            ids.sort_by_cached_key(|&id| {
+                let Range { start, end } = queries[id];
+                hilbert_index(side, start as u64, end as u64)
+            });
+        }
+    }
+
+    let mut answers: Vec<Option<S::Answer>> = (0..queries.len()).map(|_| None).collect();
+    let mut window = 0..0;
+    for id in ids {
+        let target = &queries[id];
+
+        // Grow first, so the window never has start > end
+        while window.start > target.start {
+            window.start -= 1;
+            state.add(window.start);
+        }
+        while window.end < target.end {
+            state.add(window.end);
+            window.end += 1;
+        }
+        while window.start < target.start {
+            state.remove(window.start);
+            window.start += 1;
+        }
+        while window.end > target.end {
+            window.end -= 1;
+            state.remove(window.end);
+        }
+
+        answers[id] = Some(state.answer(id));
+    }
+
+    answers
+        .into_iter()
+        .map(|answer| answer.expect("Every query is answered"))
+        .collect()
+}
+
+/// Position of `(x, y)` along the Hilbert curve filling a `side × side`
+/// square (`side` a power of two).
+fn hilbert_index(side: u64, mut x: u64, mut y: u64) -> u128 {
+    let mut index = 0_u128;
+    let mut half = side / 2;
+    while half > 0 {
+        let rx = u64::from(x & half > 0);
+        let ry = u64::from(y & half > 0);
+        index += u128::from(half) * u128::from(half) * u128::from((3 * rx) ^ ry);
+
+        // Rotate the quadrant so the curve stays continuous
+        if ry == 0 {
+            if rx == 1 {
+                x = side - 1 - x;
+                y = side - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        half /= 2;
+    }
+    index
+}
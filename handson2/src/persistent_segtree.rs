@@ -0,0 +1,339 @@
+//! Persistent (versioned) variant of the Problem 1 segment tree.
+//!
+//! [`PersistentSegTree`] never modifies a node in place: every update copies
+//! the nodes it touches (path copying) and records the new root as a fresh
+//! version, while unchanged subtrees stay shared between versions.
+
+use std::ops::Range;
+
+/// Node stored in the shared arena.
+///
+/// A child may hold values larger than its parent's `max_value`: the parent
+/// maximum acts as an implicit chmin over its whole subtree, exactly like the
+/// pending clamp of the original beats tree. `second_max` is `None` when
+/// every element of the subtree is equal.
+#[derive(Clone, Copy, Debug)]
+struct Node {
+    max_value: i64,
+    second_max: Option<i64>,
+    left: usize,
+    right: usize,
+}
+
+impl Node {
+    /// Create a leaf from a single value.
+    fn from_single(value: i64) -> Self {
+        Self {
+            max_value: value,
+            ..Self::empty()
+        }
+    }
+
+    /// Create an empty node: the children of every leaf, at index 0. It is
+    /// never read.
+    fn empty() -> Self {
+        Self {
+            max_value: 0,
+            second_max: None,
+            left: 0,
+            right: 0,
+        }
+    }
+
+    /// Build the parent of two arena nodes `(index, node)`.
+    fn merge((left_index, left): (usize, Node), (right_index, right): (usize, Node)) -> Node {
+        // `None < Some(_)`, so `max` skips missing second maximums
+        let (max_value, second_max) = if left.max_value == right.max_value {
+            (left.max_value, left.second_max.max(right.second_max))
+        } else if left.max_value > right.max_value {
+            (left.max_value, left.second_max.max(Some(right.max_value)))
+        } else {
+            (right.max_value, right.second_max.max(Some(left.max_value)))
+        };
+
+        Node {
+            max_value,
+            second_max,
+            left: left_index,
+            right: right_index,
+        }
+    }
+}
+
+/// Persistent segment tree supporting range chmin and point assignment, with
+/// range max queries on any past version.
+///
+/// Version `0` is the initial array and the `k`-th update creates version
+/// `k`. Updates always apply to the latest version. All ranges are 0-based
+/// and half-open (`start..end`).
+///
+/// A point update copies O(log n) nodes; a chmin copies the nodes Segment
+/// Tree Beats visits, i.e. amortized O(log n) over the whole history. Queries
+/// run in O(log n) and never allocate.
+///
+/// # Panics
+/// Every range method panics if `start > end` or `end > len()`, and every
+/// query panics if the version does not exist.
+///
+/// # Examples
+/// ```
+/// use handson2::persistent_segtree::PersistentSegTree;
+///
+/// let mut tree = PersistentSegTree::new(&[5, 1, 4]);
+/// let v1 = tree.chmin(0..3, 3);
+/// let v2 = tree.set(1, 7);
+/// assert_eq!(tree.max(0, 0..3), Some(5));
+/// assert_eq!(tree.max(v1, 0..3), Some(3));
+/// assert_eq!(tree.max(v2, 0..3), Some(7));
+/// ```
+#[derive(Clone, Debug)]
+pub struct PersistentSegTree {
+    n: usize,
+    nodes: Vec<Node>,
+    roots: Vec<usize>,
+}
+
+impl PersistentSegTree {
+    /// Builds version `0` over a copy of `values` in O(n).
+    pub fn new(values: &[i64]) -> Self {
+        let n = values.len();
+        let mut st = Self {
+            n,
+            nodes: Vec::with_capacity(2 * n + 1),
+            roots: Vec::new(),
+        };
+        st.nodes.push(Node::empty());
+        let root = if n > 0 { st.build(0, n - 1, values) } else { 0 };
+        st.roots.push(root);
+        st
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    /// Returns `true` if the tree holds no elements.
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// Returns the number of versions (updates performed plus one).
+    pub fn versions(&self) -> usize {
+        self.roots.len()
+    }
+
+    /// Returns the id of the latest version.
+    pub fn latest(&self) -> usize {
+        self.roots.len() - 1
+    }
+
+    /// Applies `A[k] = min(A[k], x)` for every `k` in `range` and returns the
+    /// id of the new version.
+    pub fn chmin(&mut self, range: Range<usize>, x: i64) -> usize {
+        let root = self.roots[self.latest()];
+        let root = match self.bounds(range) {
+            Some((ql, qr)) => self.range_chmin_rec(root, 0, self.n - 1, ql, qr, x),
+            None => root,
+        };
+        self.roots.push(root);
+        self.latest()
+    }
+
+    /// Sets `A[index] = value` and returns the id of the new version.
+    ///
+    /// # Panics
+    /// Panics if `index >= len()`.
+    pub fn set(&mut self, index: usize, value: i64) -> usize {
+        assert!(
+            index < self.n,
+            "Index {index} is out of bounds for length {}",
+            self.n
+        );
+        let root = self.roots[self.latest()];
+        let root = self.set_rec(root, 0, self.n - 1, index, value);
+        self.roots.push(root);
+        self.latest()
+    }
+
+    /// Returns the maximum of `range` in `version`, or `None` if the range
+    /// is empty.
+    pub fn max(&self, version: usize, range: Range<usize>) -> Option<i64> {
+        let root = self.root(version);
+        let (ql, qr) = self.bounds(range)?;
+        self.range_max_rec(root, 0, self.n - 1, ql, qr)
+    }
+
+    /// Returns the values of all elements in `version`.
+    pub fn to_vec(&self, version: usize) -> Vec<i64> {
+        let root = self.root(version);
+        let mut values = Vec::with_capacity(self.n);
+        if self.n > 0 {
+            let cap = self.nodes[root].max_value;
+            self.collect_rec(root, 0, self.n - 1, cap, &mut values);
+        }
+        values
+    }
+
+    /// Returns the root of `version`.
+    fn root(&self, version: usize) -> usize {
+        assert!(
+            version < self.roots.len(),
+            "Version {version} does not exist ({} versions)",
+            self.roots.len()
+        );
+        self.roots[version]
+    }
+
+    /// Validates a half-open range and converts it to inclusive bounds,
+    /// or `None` if it is empty.
+    fn bounds(&self, range: Range<usize>) -> Option<(usize, usize)> {
+        crate::check_range(&range, self.n);
+        (!range.is_empty()).then(|| (range.start, range.end - 1))
+    }
+
+    /// Appends a node to the arena and returns its index.
+    fn alloc(&mut self, node: Node) -> usize {
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    /// Builds a parent node from two children and appends it to the arena.
+    fn alloc_parent(&mut self, left: usize, right: usize) -> usize {
+        let node = Node::merge((left, self.nodes[left]), (right, self.nodes[right]));
+        self.alloc(node)
+    }
+
+    fn build(&mut self, left: usize, right: usize, values: &[i64]) -> usize {
+        // Leaf node
+        if left == right {
+            return self.alloc(Node::from_single(values[left]));
+        }
+
+        let mid = (left + right) / 2;
+        let left_child = self.build(left, mid, values);
+        let right_child = self.build(mid + 1, right, values);
+        self.alloc_parent(left_child, right_child)
+    }
+
+    /// Returns `child` with its maximum clamped to `cap`, copying it only if
+    /// the clamp changes it.
+    fn clamped(&mut self, child: usize, cap: i64) -> usize {
+        let node = self.nodes[child];
+        if node.max_value <= cap {
+            return child;
+        }
+        self.alloc(Node {
+            max_value: cap,
+            ..node
+        })
+    }
+
+    fn range_chmin_rec(
+        &mut self,
+        index: usize,
+        left: usize,
+        right: usize,
+        ql: usize,
+        qr: usize,
+        x: i64,
+    ) -> usize {
+        let node = self.nodes[index];
+
+        // No overlap or nothing to reduce: share the subtree
+        if right < ql || qr < left || node.max_value <= x {
+            return index;
+        }
+
+        // Total overlap and safe clamp: copy this node only
+        if ql <= left && right <= qr && node.second_max < Some(x) {
+            return self.alloc(Node {
+                max_value: x,
+                ..node
+            });
+        }
+
+        // Otherwise, copy the children with the parent's clamp applied and recurse
+        let mid = (left + right) / 2;
+        let left_child = self.clamped(node.left, node.max_value);
+        let right_child = self.clamped(node.right, node.max_value);
+
+        let left_child = self.range_chmin_rec(left_child, left, mid, ql, qr, x);
+        let right_child = self.range_chmin_rec(right_child, mid + 1, right, ql, qr, x);
+        self.alloc_parent(left_child, right_child)
+    }
+
+    fn set_rec(
+        &mut self,
+        index: usize,
+        left: usize,
+        right: usize,
+        pos: usize,
+        value: i64,
+    ) -> usize {
+        // Leaf node
+        if left == right {
+            return self.alloc(Node::from_single(value));
+        }
+
+        let node = self.nodes[index];
+        let mid = (left + right) / 2;
+        let mut left_child = self.clamped(node.left, node.max_value);
+        let mut right_child = self.clamped(node.right, node.max_value);
+
+        if pos <= mid {
+            left_child = self.set_rec(left_child, left, mid, pos, value);
+        } else {
+            right_child = self.set_rec(right_child, mid + 1, right, pos, value);
+        }
+        self.alloc_parent(left_child, right_child)
+    }
+
+    fn range_max_rec(
+        &self,
+        index: usize,
+        left: usize,
+        right: usize,
+        ql: usize,
+        qr: usize,
+    ) -> Option<i64> {
+        let node = self.nodes[index];
+
+        // No overlap
+        if right < ql || qr < left {
+            return None;
+        }
+        // Fully covered
+        if ql <= left && right <= qr {
+            return Some(node.max_value);
+        }
+
+        // Partial overlap; the node's maximum caps both children
+        let mid = (left + right) / 2;
+        let max_left = self.range_max_rec(node.left, left, mid, ql, qr);
+        let max_right = self.range_max_rec(node.right, mid + 1, right, ql, qr);
+        max_left.max(max_right).map(|max| max.min(node.max_value))
+    }
+
+    fn collect_rec(&self, index: usize, left: usize, right: usize, cap: i64, out: &mut Vec<i64>) {
+        let node = self.nodes[index];
+        let cap = cap.min(node.max_value);
+
+        // Leaf node
+        if left == right {
+            out.push(cap);
+            return;
+        }
+
+        let mid = (left + right) / 2;
+        self.collect_rec(node.left, left, mid, cap, out);
+        self.collect_rec(node.right, mid + 1, right, cap, out);
+    }
+}
+
+impl FromIterator<i64> for PersistentSegTree {
+    fn from_iter<I: IntoIterator<Item = i64>>(iter: I) -> Self {
+        let values: Vec<i64> = iter.into_iter().collect();
+        Self::new(&values)
+    }
+}
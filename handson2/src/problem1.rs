@@ -1,35 +1,48 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::Range;
+
+use crate::journal::Checkpoint;
+use crate::lazy_segtree::{Action, Element, LazyTree, Monoid, Recursive, TreeBackend};
+
+/// Solves the "Min and Max" hands-on problem on the given input stream.
+///
+/// Thin wrapper over [`SegmentTree`]: converts the 1-based inclusive ranges of
+/// the input into 0-based half-open ones.
 pub fn solve(input: &str) -> String {
     let mut iterator = input.split_whitespace().map(|s| s.parse::<i64>().unwrap());
 
     let n = iterator.next().unwrap_or(0) as usize;
     let q = iterator.next().unwrap_or(0) as usize;
 
-    let mut values = Vec::with_capacity(n);
-    for _ in 0..n {
-        values.push(iterator.next().unwrap());
-    }
-
+    let values: Vec<i64> = iterator.by_ref().take(n).collect();
     let mut tree = SegmentTree::new(&values);
     let mut output = Vec::new();
 
     for _ in 0..q {
         let op_type = iterator.next().unwrap();
 
+        // Every operation starts with a 1-based inclusive range [i, j].
+        let i = iterator.next().unwrap() as usize;
+        let j = iterator.next().unwrap() as usize;
+        let range = i - 1..j;
+
         match op_type {
-            0 => {
-                // Update(i, j, T): apply A[k] = min(A[k], T)
-                let i = iterator.next().unwrap() as usize - 1;
-                let j = iterator.next().unwrap() as usize - 1;
-                let t = iterator.next().unwrap();
-                tree.range_chmin(i, j, t);
-            }
-            1 => {
-                // Max(i, j)
-                let i = iterator.next().unwrap() as usize - 1;
-                let j = iterator.next().unwrap() as usize - 1;
-                let ans = tree.range_max(i, j);
-                output.push(ans.to_string());
-            }
+            // Update(i, j, T): apply A[k] = min(A[k], T)
+            0 => tree.chmin(range, iterator.next().unwrap()),
+            // Max(i, j)
+            1 => output.push(tree.max(range).expect("Empty Max range").to_string()),
+            // ChMax(i, j, T): apply A[k] = max(A[k], T)
+            2 => tree.chmax(range, iterator.next().unwrap()),
+            // Add(i, j, v): apply A[k] = A[k] + v
+            3 => tree.add(range, iterator.next().unwrap()),
+            // Assign(i, j, v): apply A[k] = v
+            4 => tree.assign(range, iterator.next().unwrap()),
+            // Sum(i, j)
+            5 => output.push(tree.sum(range).to_string()),
+            // Min(i, j)
+            6 => output.push(tree.min(range).expect("Empty Min range").to_string()),
             _ => panic!("Unknown operation type: {op_type}"),
         }
     }
@@ -37,202 +50,555 @@ pub fn solve(input: &str) -> String {
     output.join("\n")
 }
 
-#[derive(Clone, Copy, Debug)]
-struct Node {
-    max_value: i64,
-    second_max: i64,
+/// Aggregate of a segment for Segment Tree Beats.
+///
+/// `second_max` / `second_min` are `None` when every element of the segment
+/// is equal. All fields but `len` are meaningless when `len == 0`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Node<T> {
+    max_value: T,
+    second_max: Option<T>,
+    max_count: usize,
+    min_value: T,
+    second_min: Option<T>,
+    min_count: usize,
+    /// Wrapping sum (see [`Element`]).
+    sum: T,
+    len: usize,
 }
 
 // Node methods for Segment Tree Beats
-impl Node {
+impl<T: Element> Node<T> {
     /// Create a node from a single value.
-    fn from_single(value: i64) -> Self {
+    fn from_single(value: T) -> Self {
+        Self::uniform(value, 1)
+    }
+
+    /// Create a node whose `len` elements all equal `value`.
+    fn uniform(value: T, len: usize) -> Self {
         Self {
             max_value: value,
-            second_max: i64::MIN,
+            second_max: None,
+            max_count: len,
+            min_value: value,
+            second_min: None,
+            min_count: len,
+            sum: value.wrapping_mul(T::from_len(len)),
+            len,
         }
     }
 
     /// Create an empty node (used for initialization and in special cases).
     fn empty() -> Self {
-        Self {
-            max_value: i64::MIN,
-            second_max: i64::MIN,
-        }
+        Self::uniform(T::ZERO, 0)
     }
 
     /// Merge two child nodes into a parent node.
-    fn merge(left: Node, right: Node) -> Node {
+    fn merge(left: &Self, right: &Self) -> Self {
         // If one node is empty, return the other
-        if left.max_value == i64::MIN {
-            return right;
+        if left.len == 0 {
+            return *right;
         }
-        if right.max_value == i64::MIN {
-            return left;
+        if right.len == 0 {
+            return *left;
         }
 
-        // Both valid; compare max_value
-        if left.max_value == right.max_value {
-            Node {
-                max_value: left.max_value,
-                second_max: left.second_max.max(right.second_max),
-            }
+        // Both valid; compare max_value (`None < Some(_)`, so `max` skips
+        // missing second maximums)
+        let (max_value, second_max, max_count) = if left.max_value == right.max_value {
+            (
+                left.max_value,
+                left.second_max.max(right.second_max),
+                left.max_count + right.max_count,
+            )
         } else if left.max_value > right.max_value {
-            Node {
-                max_value: left.max_value,
-                second_max: left.second_max.max(right.max_value),
-            }
+            (
+                left.max_value,
+                left.second_max.max(Some(right.max_value)),
+                left.max_count,
+            )
         } else {
-            Node {
-                max_value: right.max_value,
-                second_max: right.second_max.max(left.max_value),
-            }
+            (
+                right.max_value,
+                right.second_max.max(Some(left.max_value)),
+                right.max_count,
+            )
+        };
+
+        // Symmetrically for min_value, skipping missing second minimums
+        let min_of = |a: Option<T>, b: Option<T>| a.into_iter().chain(b).min();
+        let (min_value, second_min, min_count) = if left.min_value == right.min_value {
+            (
+                left.min_value,
+                min_of(left.second_min, right.second_min),
+                left.min_count + right.min_count,
+            )
+        } else if left.min_value < right.min_value {
+            (
+                left.min_value,
+                min_of(left.second_min, Some(right.min_value)),
+                left.min_count,
+            )
+        } else {
+            (
+                right.min_value,
+                min_of(right.second_min, Some(left.min_value)),
+                right.min_count,
+            )
+        };
+
+        Node {
+            max_value,
+            second_max,
+            max_count,
+            min_value,
+            second_min,
+            min_count,
+            sum: left.sum.wrapping_add(right.sum),
+            len: left.len + right.len,
         }
     }
 }
 
-/// Segment Tree Beats implementation.
-struct SegmentTree {
-    n: usize,
-    tree: Vec<Node>,
+/// Monoid of [`Node`] aggregates.
+struct Beats<T>(PhantomData<T>);
+
+impl<T: Element> Monoid for Beats<T> {
+    type Value = Node<T>;
+
+    fn identity() -> Node<T> {
+        Node::empty()
+    }
+
+    fn combine(left: &Node<T>, right: &Node<T>) -> Node<T> {
+        Node::merge(left, right)
+    }
 }
 
-impl SegmentTree {
-    fn new(values: &[i64]) -> Self {
-        let n = values.len();
-        let size = 4 * n.max(1);
-        let mut st = Self {
-            n,
-            tree: vec![Node::empty(); size],
-        };
-        if n > 0 {
-            st.build(1, 0, n - 1, values);
+/// Lazy tag `v -> clamp(v + add, lo, hi)`, with `lo <= hi` and `None`
+/// meaning unbounded.
+///
+/// Chmin, chmax, add and assign are all special cases, and the composition
+/// of two such functions is again of this form.
+///
+/// The pending addition is `add + carry · 2^bits`: adds separated by a clamp
+/// (`add(MAX)`, `chmin(0)`, `add(1)`) may sum past the ends of `T` even
+/// though every value stays in range. `add` alone is the addition modulo
+/// `2^bits`, which is all the wrapping sums need.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Clamp<T> {
+    add: T,
+    carry: i8,
+    lo: Option<T>,
+    hi: Option<T>,
+}
+
+impl<T: Element> Clamp<T> {
+    fn chmin(x: T) -> Self {
+        Self {
+            add: T::ZERO,
+            carry: 0,
+            lo: None,
+            hi: Some(x),
         }
-        st
     }
 
-    fn build(&mut self, index: usize, left: usize, right: usize, values: &[i64]) {
-        // Leaf node
-        if left == right {
-            self.tree[index] = Node::from_single(values[left]);
-            return;
+    fn chmax(x: T) -> Self {
+        Self {
+            add: T::ZERO,
+            carry: 0,
+            lo: Some(x),
+            hi: None,
         }
+    }
 
-        let mid = (left + right) / 2;
-        let left_child = index * 2;
-        let right_child = left_child + 1;
+    fn add(value: T) -> Self {
+        Self {
+            add: value,
+            carry: 0,
+            lo: None,
+            hi: None,
+        }
+    }
 
-        self.build(left_child, left, mid, values);
-        self.build(right_child, mid + 1, right, values);
+    fn assign(value: T) -> Self {
+        Self {
+            add: T::ZERO,
+            carry: 0,
+            lo: Some(value),
+            hi: Some(value),
+        }
+    }
 
-        self.tree[index] = Node::merge(self.tree[left_child], self.tree[right_child]);
+    /// Returns `value` plus the pending addition, saturated to `T`.
+    ///
+    /// A sum past either end of `T` is always brought back by the clamp, so
+    /// the clamped result is still exact.
+    fn shift(&self, value: T) -> T {
+        let (sum, overflow) = value.overflowing_add(self.add);
+        match self.carry.saturating_add(carry(self.add, overflow)).cmp(&0) {
+            Ordering::Less => T::MIN,
+            Ordering::Equal => sum,
+            Ordering::Greater => T::MAX,
+        }
     }
 
-    /// Push the parent's max_value constraint down to children.
-    fn push_down(&mut self, index: usize) {
-        let parent_node = self.tree[index];
-        let left_child = index * 2;
-        let right_child = left_child + 1;
+    /// Evaluates the tag on `value`.
+    fn eval(&self, value: T) -> T {
+        let value = self.shift(value);
+        let value = self.lo.map_or(value, |lo| value.max(lo));
+        self.hi.map_or(value, |hi| value.min(hi))
+    }
 
-        // If child's maximum is too large, clamp it down
-        if self.tree[left_child].max_value > parent_node.max_value {
-            self.clamp_node(left_child, parent_node.max_value);
-        }
-        if self.tree[right_child].max_value > parent_node.max_value {
-            self.clamp_node(right_child, parent_node.max_value);
+    /// Turns a tag sending every value to the same bound into an assign, so
+    /// that repeated clamped additions cannot grow `carry`.
+    fn normalized(self) -> Self {
+        match (self.lo, self.hi) {
+            (_, Some(hi)) if self.shift(T::MIN) >= hi => Self::assign(hi),
+            (Some(lo), _) if self.shift(T::MAX) <= lo => Self::assign(lo),
+            _ => self,
         }
     }
+}
 
-    /// Apply chmin(x) to a node that satisfies: second_max < x < max_value.
-    fn clamp_node(&mut self, index: usize, new_max: i64) {
-        let node = &mut self.tree[index];
-        if new_max >= node.max_value {
-            return; // this would not change anything
-        }
-        node.max_value = new_max;
+/// Carry out of `value + add`: `±1` if it overflowed, in the direction of
+/// `add`.
+fn carry<T: Element>(add: T, overflow: bool) -> i8 {
+    match (overflow, add < T::ZERO) {
+        (false, _) => 0,
+        (true, true) => -1,
+        (true, false) => 1,
     }
+}
 
-    /// Wrapper for range chmin.
-    fn range_chmin(&mut self, ql: usize, qr: usize, x: i64) {
-        if self.n == 0 {
-            return;
-        }
-        self.range_chmin_rec(1, 0, self.n - 1, ql, qr, x);
+/// Segment Tree Beats action applying [`Clamp`] tags.
+struct ClampAction<T>(PhantomData<T>);
+
+impl<T: Element> Action<Beats<T>> for ClampAction<T> {
+    type Tag = Clamp<T>;
+
+    fn identity() -> Clamp<T> {
+        Clamp::add(T::ZERO)
     }
 
-    fn range_chmin_rec(
-        &mut self,
-        index: usize,
-        left: usize,
-        right: usize,
-        ql: usize,
-        qr: usize,
-        x: i64,
-    ) {
-        let node = self.tree[index];
+    fn compose(outer: &Clamp<T>, inner: &Clamp<T>) -> Clamp<T> {
+        // clamp(clamp(v + a1, lo1, hi1) + a2, lo2, hi2)
+        //   = clamp(v + a1 + a2, clamp(lo1 + a2, lo2, hi2), clamp(hi1 + a2, lo2, hi2))
+        // where an unbounded inner side stays unbounded unless `outer` bounds it.
+        let (add, overflow) = inner.add.overflowing_add(outer.add);
+        Clamp {
+            add,
+            carry: inner
+                .carry
+                .saturating_add(outer.carry)
+                .saturating_add(carry(outer.add, overflow)),
+            lo: inner.lo.map(|lo| outer.eval(lo)).or(outer.lo),
+            hi: inner.hi.map(|hi| outer.eval(hi)).or(outer.hi),
+        }
+        .normalized()
+    }
 
-        // No overlap or nothing to reduce
-        if right < ql || qr < left || node.max_value <= x {
-            return;
+    fn apply(tag: &Clamp<T>, node: &Node<T>) -> Option<Node<T>> {
+        if node.len == 0 {
+            return Some(*node);
         }
 
-        // Fully covered and can apply clamp directly
-        if ql <= left && right <= qr && node.second_max < x && x < node.max_value {
-            self.clamp_node(index, x);
-            return;
+        // Constant tag (assign) or a single distinct value: everything collapses.
+        if (tag.lo.is_some() && tag.lo == tag.hi) || node.max_value == node.min_value {
+            return Some(Node::uniform(tag.eval(node.max_value), node.len));
         }
 
-        // Otherwise, push constraints down before recursion
-        if left != right {
-            self.push_down(index);
-            let mid = (left + right) / 2;
-            let left_child = index * 2;
-            let right_child = left_child + 1;
+        let new_max = tag.eval(node.max_value);
+        let new_min = tag.eval(node.min_value);
 
-            self.range_chmin_rec(left_child, left, mid, ql, qr, x);
-            self.range_chmin_rec(right_child, mid + 1, right, ql, qr, x);
+        // Two distinct values: both groups are known exactly.
+        if node.second_max == Some(node.min_value) {
+            if new_max == new_min {
+                return Some(Node::uniform(new_max, node.len));
+            }
+            return Some(Node {
+                max_value: new_max,
+                second_max: Some(new_min),
+                min_value: new_min,
+                second_min: Some(new_max),
+                sum: new_max
+                    .wrapping_mul(T::from_len(node.max_count))
+                    .wrapping_add(new_min.wrapping_mul(T::from_len(node.min_count))),
+                ..*node
+            });
+        }
 
-            self.tree[index] = Node::merge(self.tree[left_child], self.tree[right_child]);
-        } else {
-            // Leaf case — clamp it
-            self.clamp_node(index, x);
+        // Beats condition: only the maximums and minimums may be clamped,
+        // every value in [second_min, second_max] must simply shift by `add`.
+        // A shift past the ends of `T` saturates and fails the condition, so
+        // the stored values are exact.
+        let second_max = tag.shift(node.second_max.expect("At least three distinct values"));
+        let second_min = tag.shift(node.second_min.expect("At least three distinct values"));
+        if tag.hi.is_some_and(|hi| hi <= second_max) || tag.lo.is_some_and(|lo| lo >= second_min) {
+            return None;
         }
+
+        // Shift everything, then replace the two extreme groups. The shifted
+        // extremes may leave the range of `T` before clamping, but they only
+        // feed the wrapping sum correction.
+        let count = |len| T::from_len(len);
+        let shifted_max = node.max_value.wrapping_add(tag.add);
+        let shifted_min = node.min_value.wrapping_add(tag.add);
+        Some(Node {
+            max_value: new_max,
+            second_max: Some(second_max),
+            min_value: new_min,
+            second_min: Some(second_min),
+            sum: node
+                .sum
+                .wrapping_add(tag.add.wrapping_mul(count(node.len)))
+                .wrapping_sub(shifted_max.wrapping_mul(count(node.max_count)))
+                .wrapping_add(new_max.wrapping_mul(count(node.max_count)))
+                .wrapping_sub(shifted_min.wrapping_mul(count(node.min_count)))
+                .wrapping_add(new_min.wrapping_mul(count(node.min_count))),
+            ..*node
+        })
     }
+}
 
-    /// Wrapper for range max query.
-    fn range_max(&mut self, ql: usize, qr: usize) -> i64 {
-        if self.n == 0 {
-            return i64::MIN;
-        }
-        self.range_max_rec(1, 0, self.n - 1, ql, qr)
+/// Result of [`SegmentTree::argmax`]: an extreme value of a range and where
+/// it occurs.
+///
+/// Positions are `P` and counts `C`, so that
+/// [`CoverageIndex`](crate::coverage_index::CoverageIndex) can report `u64`
+/// positions and up to 2⁶⁴ of them as an
+/// [`Extremum`](crate::coverage_index::Extremum).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ArgMax<T = i64, P = usize, C = usize> {
+    /// The extreme value of the range.
+    pub value: T,
+    /// Leftmost position holding `value`.
+    pub first: P,
+    /// Rightmost position holding `value`.
+    pub last: P,
+    /// Number of positions holding `value`.
+    pub count: C,
+}
+
+/// Segment Tree Beats over [`Element`] values (Ji Ruyi's "beats" with max/min counts and sum).
+///
+/// Supports range chmin, chmax, add and assign updates together with range
+/// sum, min and max queries. All ranges are 0-based and half-open
+/// (`start..end`). Updates run in amortized O(log² n), queries in O(log n).
+///
+/// Built on a generic lazy tree, with `clamp(v + add, lo, hi)` tags that
+/// fail (and descend) only where the beats condition does not hold. The tree
+/// is the recursive [`LazySegTree`](crate::lazy_segtree::LazySegTree) unless
+/// `B` selects another [`TreeBackend`], e.g.
+/// [`Iterative`](crate::iter_segtree::Iterative).
+/// Queries take `&mut self` because they push pending tags down the tree.
+///
+/// # Panics
+/// Every range method panics if `start > end` or `end > len()`.
+///
+/// # Examples
+/// ```
+/// use handson2::problem1::SegmentTree;
+///
+/// let mut tree = SegmentTree::new(&[5, 1, 4, 2]);
+/// tree.chmin(0..3, 3);
+/// assert_eq!(tree.max(0..4), Some(3));
+/// assert_eq!(tree.to_vec(), vec![3, 1, 3, 2]);
+///
+/// // Same operations on the iterative backend
+/// use handson2::iter_segtree::Iterative;
+///
+/// let mut tree = SegmentTree::<i64, Iterative>::with_backend(&[5, 1, 4, 2]);
+/// tree.chmin(0..3, 3);
+/// assert_eq!(tree.to_vec(), vec![3, 1, 3, 2]);
+/// ```
+#[derive(Clone)]
+pub struct SegmentTree<T: Element = i64, B: TreeBackend = Recursive> {
+    tree: B::Tree<Beats<T>, ClampAction<T>>,
+}
+
+impl<T: Element> SegmentTree<T> {
+    /// Builds the tree over a copy of `values` in O(n).
+    pub fn new(values: &[T]) -> Self {
+        Self::with_backend(values)
     }
+}
 
-    fn range_max_rec(
-        &mut self,
-        index: usize,
-        left: usize,
-        right: usize,
-        ql: usize,
-        qr: usize,
-    ) -> i64 {
-        // No overlap
-        if right < ql || qr < left {
-            return i64::MIN;
-        }
-        // Fully covered
-        if ql <= left && right <= qr {
-            return self.tree[index].max_value;
-        }
+impl<T: Element, B: TreeBackend> SegmentTree<T, B> {
+    /// Builds the tree over a copy of `values` in O(n) on the backend `B`.
+    pub fn with_backend(values: &[T]) -> Self {
+        values.iter().copied().collect()
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// Returns `true` if the tree holds no elements.
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Applies `A[k] = min(A[k], x)` for every `k` in `range`.
+    pub fn chmin(&mut self, range: Range<usize>, x: T) {
+        self.tree.apply(range, &Clamp::chmin(x));
+    }
 
-        // Partial overlap; push down and recurse
-        self.push_down(index);
+    /// Applies `A[k] = max(A[k], x)` for every `k` in `range`.
+    pub fn chmax(&mut self, range: Range<usize>, x: T) {
+        self.tree.apply(range, &Clamp::chmax(x));
+    }
+
+    /// Applies `A[k] = A[k] + value` for every `k` in `range`.
+    ///
+    /// Every `A[k]` must stay within `T`.
+    pub fn add(&mut self, range: Range<usize>, value: T) {
+        self.tree.apply(range, &Clamp::add(value));
+    }
 
-        let mid = (left + right) / 2;
-        let left_child = index * 2;
-        let right_child = left_child + 1;
+    /// Applies `A[k] = value` for every `k` in `range`.
+    pub fn assign(&mut self, range: Range<usize>, value: T) {
+        self.tree.apply(range, &Clamp::assign(value));
+    }
 
-        let max_left = self.range_max_rec(left_child, left, mid, ql, qr);
-        let max_right = self.range_max_rec(right_child, mid + 1, right, ql, qr);
-        max_left.max(max_right)
+    /// Returns the maximum of `range`, or `None` if it is empty.
+    pub fn max(&mut self, range: Range<usize>) -> Option<T> {
+        self.query(range).map(|node| node.max_value)
+    }
+
+    /// Returns the minimum of `range`, or `None` if it is empty.
+    pub fn min(&mut self, range: Range<usize>) -> Option<T> {
+        self.query(range).map(|node| node.min_value)
+    }
+
+    /// Returns the sum of `range` (`0` if it is empty).
+    ///
+    /// The sum wraps around on overflow, so it is exact whenever the true sum
+    /// fits in `T`, even if partial sums do not.
+    pub fn sum(&mut self, range: Range<usize>) -> T {
+        self.query(range).map_or(T::ZERO, |node| node.sum)
+    }
+
+    /// Returns how many positions of `range` hold its maximum (`0` if it is empty).
+    pub fn max_count(&mut self, range: Range<usize>) -> usize {
+        self.query(range).map_or(0, |node| node.max_count)
+    }
+
+    /// Returns the maximum of `range` with the leftmost and rightmost
+    /// positions attaining it and their count, or `None` if it is empty.
+    ///
+    /// Runs in O(log n): one query plus two threshold descents.
+    pub fn argmax(&mut self, range: Range<usize>) -> Option<ArgMax<T>> {
+        let node = self.query(range.clone())?;
+        let value = node.max_value;
+        let first = self.first_at_least(range.start, value)?;
+        let last = self.last_at_least(range.end, value)?;
+        Some(ArgMax {
+            value,
+            first,
+            last,
+            count: node.max_count,
+        })
+    }
+
+    /// Returns the largest `end` such that `pred(max(start..end))` holds, or
+    /// `start` if it fails on `A[start]` alone.
+    ///
+    /// `pred` receives the maximum of non-empty ranges and must be monotone:
+    /// once it fails for a range, it fails for every longer one. For example
+    /// `max_right(l, |max| max < x)` is the largest `r` with `max(l..r) < x`.
+    /// Runs in O(log n) and accounts for pending chmin tags.
+    ///
+    /// # Panics
+    /// Panics if `start > len()`.
+    pub fn max_right<P: Fn(T) -> bool>(&mut self, start: usize, pred: P) -> usize {
+        self.tree
+            .max_right(start, |node| node.len == 0 || pred(node.max_value))
+    }
+
+    /// Returns the smallest `start` such that `pred(max(start..end))` holds, or
+    /// `end` if it fails on `A[end - 1]` alone.
+    ///
+    /// Mirror of [`SegmentTree::max_right`], growing the range to the left.
+    ///
+    /// # Panics
+    /// Panics if `end > len()`.
+    pub fn min_left<P: Fn(T) -> bool>(&mut self, end: usize, pred: P) -> usize {
+        self.tree
+            .min_left(end, |node| node.len == 0 || pred(node.max_value))
+    }
+
+    /// Returns the first index `k >= start` with `A[k] >= x`, if any.
+    ///
+    /// # Panics
+    /// Panics if `start > len()`.
+    pub fn first_at_least(&mut self, start: usize, x: T) -> Option<usize> {
+        let end = self.max_right(start, |max| max < x);
+        (end < self.len()).then_some(end)
+    }
+
+    /// Returns the last index `k < end` with `A[k] >= x`, if any.
+    ///
+    /// # Panics
+    /// Panics if `end > len()`.
+    pub fn last_at_least(&mut self, end: usize, x: T) -> Option<usize> {
+        let start = self.min_left(end, |max| max < x);
+        start.checked_sub(1)
+    }
+
+    /// Opens a checkpoint at the current state; see [`journal`](crate::journal).
+    pub fn checkpoint(&mut self) -> Checkpoint {
+        self.tree.checkpoint()
+    }
+
+    /// Restores the state at `checkpoint`; see [`journal`](crate::journal).
+    ///
+    /// # Panics
+    /// Panics if `checkpoint` was already released.
+    pub fn rollback(&mut self, checkpoint: Checkpoint) {
+        self.tree.rollback(checkpoint);
+    }
+
+    /// Keeps the changes made since `checkpoint`; see [`journal`](crate::journal).
+    ///
+    /// # Panics
+    /// Panics if `checkpoint` was already released.
+    pub fn commit(&mut self, checkpoint: Checkpoint) {
+        self.tree.commit(checkpoint);
+    }
+
+    /// Returns the current values of all elements.
+    pub fn to_vec(&mut self) -> Vec<T> {
+        self.tree
+            .to_vec()
+            .iter()
+            .map(|node| node.max_value)
+            .collect()
+    }
+
+    /// Returns the aggregate of `range`, or `None` if it is empty.
+    fn query(&mut self, range: Range<usize>) -> Option<Node<T>> {
+        let node = self.tree.query(range);
+        (node.len > 0).then_some(node)
+    }
+}
+
+impl<T: Element, B: TreeBackend> fmt::Debug for SegmentTree<T, B>
+where
+    B::Tree<Beats<T>, ClampAction<T>>: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SegmentTree")
+            .field("tree", &self.tree)
+            .finish()
+    }
+}
+
+impl<T: Element, B: TreeBackend> FromIterator<T> for SegmentTree<T, B> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self {
+            tree: iter.into_iter().map(Node::from_single).collect(),
+        }
     }
 }
@@ -1,209 +1,890 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Range, RangeInclusive};
+use std::str::{FromStr, SplitWhitespace};
+
+use crate::coverage_index::CoverageIndex;
+use crate::fenwick::RangeAddFenwick;
+use crate::iter_segtree::Iterative;
+use crate::lazy_segtree::{
+    Add, LazySegTree, LazyTree, MinMax, MinMaxValue, Recursive, TreeBackend,
+};
+use crate::mo::{self, MoState, Order};
+use crate::sparse_segtree::SparseSegTree;
+use crate::sparse_table::SparseTable;
+
+/// Strategy used by [`solve_with`] to compute the coverage and answer the
+/// queries. All backends give identical answers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Backend {
+    /// Difference array and min/max tree over every coordinate from
+    /// the smallest to the largest: O(max - min) memory.
+    Dense,
+    /// Sparse segment tree over `0..=u64::MAX`: O(n log U) memory.
+    Sparse,
+    /// Min/max tree over the O(n + m) elementary intervals delimited by the
+    /// segment endpoints and query bounds: O(n + m) memory.
+    #[default]
+    Compressed,
+    /// Compressed coverage indexed by value: each query is one binary search
+    /// in the sorted positions holding `k`, O(log(n + m)) in the worst case.
+    PositionLists,
+    /// Offline Mo's algorithm over the compressed coverage, counting how
+    /// many intervals of the window hold each value: O((n + m) √m) overall,
+    /// independent of the coverage pattern.
+    Mo,
+    /// Sparse table of min/max over the compressed coverage: O(1) per range
+    /// aggregate, searched like the tree but without pushing lazy tags.
+    SparseTable,
+    /// Same as [`Compressed`](Self::Compressed), on the iterative
+    /// [`IterLazySegTree`](crate::iter_segtree::IterLazySegTree).
+    Iterative,
+}
+
+/// Interval convention of the segments and query ranges of an IsThere input.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Convention {
+    /// `[L, R]`: both endpoints belong to the range, which needs `L ≤ R`.
+    #[default]
+    Closed,
+    /// `[L, R)`: `R` is excluded; `L ≤ R` is still required, and `L = R`
+    /// is an empty range.
+    HalfOpen,
+}
+
+/// Error returned by [`try_solve_with`] when the input is not a valid
+/// IsThere input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputError {
+    /// The input ended before every segment and query was read.
+    UnexpectedEof,
+    /// A token on the given (1-based) line is not a valid integer.
+    InvalidNumber { line: usize, token: String },
+    /// The given (1-based) line has a token after its last expected value.
+    TrailingToken { line: usize, token: String },
+    /// The segment at the given (0-based) index has `L > R`.
+    InvalidSegment {
+        index: usize,
+        left: i128,
+        right: i128,
+    },
+    /// The query at the given (0-based) index has `i > j`.
+    InvalidQuery {
+        index: usize,
+        left: i128,
+        right: i128,
+    },
+    /// The smallest and largest coordinates are more than `u64::MAX` apart.
+    SpanTooLarge { min: i128, max: i128 },
+    /// The given (1-based) line of a dynamic stream is not `add`, `remove`
+    /// or `isthere`.
+    UnknownOperation { line: usize, name: String },
+    /// The dynamic operation at the given (0-based) index has `L > R`
+    /// (`i > j` for `isthere`).
+    InvalidOperation {
+        index: usize,
+        name: String,
+        left: i128,
+        right: i128,
+    },
+    /// The `remove` at the given (0-based) index has no matching `add`.
+    NotAdded {
+        index: usize,
+        left: i128,
+        right: i128,
+    },
+    /// The positive (or the negative) weights of the segments covering the
+    /// given position add up beyond `i64`.
+    CoverageOverflow { position: i128 },
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEof => write!(f, "unexpected end of input"),
+            Self::InvalidNumber { line, token } => {
+                write!(f, "line {line}: {token:?} is not a valid integer")
+            }
+            Self::TrailingToken { line, token } => {
+                write!(f, "line {line}: unexpected trailing token {token:?}")
+            }
+            Self::InvalidSegment { index, left, right } => {
+                write!(f, "segment {index} has L = {left} > R = {right}")
+            }
+            Self::InvalidQuery { index, left, right } => {
+                write!(f, "query {index} has i = {left} > j = {right}")
+            }
+            Self::SpanTooLarge { min, max } => {
+                write!(
+                    f,
+                    "coordinates from {min} to {max} span more than 2^64 positions"
+                )
+            }
+            Self::UnknownOperation { line, name } => {
+                write!(f, "line {line}: unknown operation {name:?}")
+            }
+            Self::InvalidOperation {
+                index,
+                name,
+                left,
+                right,
+            } => {
+                let (left_name, right_name) = if name == "isthere" {
+                    ("i", "j")
+                } else {
+                    ("L", "R")
+                };
+                write!(
+                    f,
+                    "operation {index} ({name} {left} {right}) has {left_name} = {left} > {right_name} = {right}"
+                )
+            }
+            Self::NotAdded { index, left, right } => {
+                write!(
+                    f,
+                    "operation {index} (remove {left} {right}) removes a segment that was not added"
+                )
+            }
+            Self::CoverageOverflow { position } => {
+                write!(
+                    f,
+                    "the weights covering position {position} add up beyond i64"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+/// Solves an IsThere input with the default [`Backend`].
 pub fn solve(input: &str) -> String {
-    let mut iterator = input.split_whitespace().map(|s| s.parse::<i64>().unwrap());
+    solve_with(input, Backend::default())
+}
+
+/// Solves an IsThere input with the given `backend`, reading every range as
+/// closed (`[L, R]`).
+///
+/// # Panics
+/// Panics with the [`InputError`] message if the input is invalid.
+pub fn solve_with(input: &str, backend: Backend) -> String {
+    try_solve_with(input, backend, Convention::Closed)
+        .unwrap_or_else(|err| panic!("Invalid IsThere input: {err}"))
+}
+
+/// Solves an IsThere input with the given `backend`, reading the segments
+/// and query ranges with the given `convention`.
+///
+/// Coordinates are signed: any integers work as long as the smallest and
+/// largest are at most `u64::MAX` apart. They are shifted so that the
+/// smallest becomes `0`, which changes no answer.
+pub fn try_solve_with(
+    input: &str,
+    backend: Backend,
+    convention: Convention,
+) -> Result<String, InputError> {
+    let (segments, queries) = parse_input(input, convention)?;
+
+    // Edge case: if there is nothing at all, return empty string
+    if segments.is_empty() && queries.is_empty() {
+        return Ok(String::new());
+    }
+
+    // Empty (half-open) query ranges are answered `0` without the backend
+    let ranges: Vec<Query> = queries.iter().flatten().copied().collect();
+    let answers = match backend {
+        Backend::Dense => {
+            // Queries may refer to positions beyond the last segment endpoint.
+            let max_coord = segments
+                .iter()
+                .map(|seg| seg.right)
+                .chain(ranges.iter().map(|query| query.right))
+                .max()
+                .unwrap_or(0);
+            solve_dense(&segments, &ranges, max_coord as usize)
+        }
+        Backend::Sparse => solve_sparse(&segments, &ranges),
+        Backend::Compressed => solve_compressed::<Recursive>(&segments, &ranges),
+        Backend::PositionLists => solve_position_lists(&segments, &ranges),
+        Backend::Mo => solve_mo(&segments, &ranges),
+        Backend::SparseTable => solve_sparse_table(&segments, &ranges),
+        Backend::Iterative => solve_compressed::<Iterative>(&segments, &ranges),
+    };
+
+    let mut answers = answers.into_iter();
+    Ok(queries
+        .iter()
+        .map(|query| query.is_some_and(|_| answers.next().expect("One answer per query")))
+        .map(|exists| if exists { "1" } else { "0" })
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+/// Returns the segments of an IsThere input as inclusive ranges, in input
+/// order, e.g. to build an [`IntervalTree`](crate::interval_tree::IntervalTree)
+/// or a [`CoverageIndex`] over them. Weights are dropped.
+///
+/// # Panics
+/// Panics if the input is invalid or a coordinate is outside `0..=u64::MAX`.
+pub fn parse_segments(input: &str) -> Vec<RangeInclusive<u64>> {
+    parse_weighted_segments(input)
+        .into_iter()
+        .map(|(seg, _)| seg)
+        .collect()
+}
+
+/// Returns the segments of an IsThere input with their weights, in input
+/// order, e.g. to build a [`CoverageIndex::weighted`].
+///
+/// # Panics
+/// Panics if the input is invalid or a coordinate is outside `0..=u64::MAX`.
+pub fn parse_weighted_segments(input: &str) -> Vec<(RangeInclusive<u64>, i64)> {
+    let coordinate =
+        |x: i128| u64::try_from(x).unwrap_or_else(|_| panic!("Coordinate {x} does not fit in u64"));
+    read_input(input, Convention::Closed)
+        .unwrap_or_else(|err| panic!("Invalid IsThere input: {err}"))
+        .0
+        .into_iter()
+        .map(|(left, right, weight)| (coordinate(left)..=coordinate(right), weight))
+        .collect()
+}
+
+/// Segments and queries of an IsThere input, in input coordinates.
+///
+/// Segments are closed `(L, R, W)` triples, empty ones dropped; queries are
+/// closed `(i, j, k)` triples, or `None` if the range is empty.
+type RawInput = (Vec<(i128, i128, i64)>, Vec<Option<(i128, i128, i64)>>);
+
+/// Reads the `n` segments and `m` queries of an IsThere input.
+///
+/// The header, every segment and every query sit on their own line, and a
+/// line with extra tokens is rejected. Every segment line is `L R` or
+/// `L R W`: a segment adds its weight `W` (default `1`, possibly negative) to
+/// the coverage of each of its positions.
+fn read_input(input: &str, convention: Convention) -> Result<RawInput, InputError> {
+    // One segment or query per line, so that the weight column can be omitted
+    let mut lines = Line::split(input);
 
     // n = number of segments, m = number of queries
-    let n = iterator.next().unwrap_or(0) as usize;
-    let m = iterator.next().unwrap_or(0) as usize;
+    let Some(mut header) = lines.next() else {
+        return Ok((Vec::new(), Vec::new()));
+    };
+    let n: usize = header.value()?;
+    let m: usize = header.optional()?.unwrap_or(0);
+    header.finish()?;
+    let mut next_line = || lines.next().ok_or(InputError::UnexpectedEof);
 
     // Read segments
     let mut segments = Vec::with_capacity(n);
-    let mut max_coord: usize = 0;
 
-    for _ in 0..n {
-        let left = iterator.next().unwrap() as usize;
-        let right = iterator.next().unwrap() as usize;
-        segments.push(Segment { left, right });
-
-        if right > max_coord {
-            max_coord = right;
+    for index in 0..n {
+        let mut line = next_line()?;
+        let left = line.value()?;
+        let right = line.value()?;
+        let weight = line.optional()?.unwrap_or(1);
+        line.finish()?;
+        if left > right {
+            return Err(InputError::InvalidSegment { index, left, right });
+        }
+        if let Some(right) = convention.last(left, right) {
+            segments.push((left, right, weight));
         }
     }
 
     // Read queries
     let mut queries = Vec::with_capacity(m);
 
-    for _ in 0..m {
-        let left = iterator.next().unwrap() as usize;
-        let right = iterator.next().unwrap() as usize;
-        let k = iterator.next().unwrap();
+    for index in 0..m {
+        let mut line = next_line()?;
+        let left = line.value()?;
+        let right = line.value()?;
+        let k = line.value()?;
+        line.finish()?;
+        if left > right {
+            return Err(InputError::InvalidQuery { index, left, right });
+        }
+
+        queries.push(convention.last(left, right).map(|right| (left, right, k)));
+    }
+
+    Ok((segments, queries))
+}
+
+/// Reads an IsThere input and shifts its coordinates so that the smallest
+/// one becomes `0`.
+fn parse_input(
+    input: &str,
+    convention: Convention,
+) -> Result<(Vec<Segment>, Vec<Option<Query>>), InputError> {
+    let (segments, queries) = read_input(input, convention)?;
+
+    let bounds = segments
+        .iter()
+        .map(|&(left, right, _)| (left, right))
+        .chain(
+            queries
+                .iter()
+                .flatten()
+                .map(|&(left, right, _)| (left, right)),
+        );
+    let (min, max) = span(bounds);
+    let shifted = shifter((min, max));
+
+    let segments: Vec<Segment> = segments
+        .into_iter()
+        .map(|(left, right, weight)| {
+            Ok(Segment {
+                left: shifted(left)?,
+                right: shifted(right)?,
+                weight,
+            })
+        })
+        .collect::<Result<_, _>>()?;
+    let queries = queries
+        .into_iter()
+        .map(|query| {
+            query
+                .map(|(left, right, k)| {
+                    Ok(Query {
+                        left: shifted(left)?,
+                        right: shifted(right)?,
+                        k,
+                    })
+                })
+                .transpose()
+        })
+        .collect::<Result<_, _>>()?;
+    check_weights(&segments).map_err(|x| InputError::CoverageOverflow {
+        position: i128::from(x) + min,
+    })?;
+    Ok((segments, queries))
+}
+
+/// Checks that the positive and the negative weights covering each position
+/// add up within `i64`, or returns the first (shifted) position where they
+/// do not.
+///
+/// Every backend sums some subset of the weights covering a position (the
+/// sparse tree keeps partial sums in its nodes), and each such sum lies
+/// between these two totals.
+fn check_weights(segments: &[Segment]) -> Result<(), u64> {
+    let points = breakpoints(segments.iter().map(|seg| (seg.left, seg.right)));
+    for positive in [true, false] {
+        let signed = segments
+            .iter()
+            .filter(|seg| (seg.weight > 0) == positive)
+            .map(|seg| (seg.left, seg.right, seg.weight));
+        interval_coverage(&points, signed)?;
+    }
+    Ok(())
+}
+
+/// Returns the smallest left and largest right bound of `bounds` (`0` if
+/// there are none).
+fn span<I>(bounds: I) -> (i128, i128)
+where
+    I: Iterator<Item = (i128, i128)> + Clone,
+{
+    let min = bounds.clone().map(|(left, _)| left).min().unwrap_or(0);
+    let max = bounds.map(|(_, right)| right).max().unwrap_or(0);
+    (min, max)
+}
+
+/// Returns the shift mapping `min` to `0`, which fails if a shifted
+/// coordinate does not fit in `u64`.
+fn shifter((min, max): (i128, i128)) -> impl Fn(i128) -> Result<u64, InputError> {
+    move |x: i128| {
+        x.checked_sub(min)
+            .and_then(|x| u64::try_from(x).ok())
+            .ok_or(InputError::SpanTooLarge { min, max })
+    }
+}
+
+impl Convention {
+    /// Last position of the range `L..R` (closed or half-open, `L ≤ R`), or
+    /// `None` if it is empty.
+    fn last(self, left: i128, right: i128) -> Option<i128> {
+        match self {
+            Convention::Closed => Some(right),
+            Convention::HalfOpen => (left < right).then(|| right - 1),
+        }
+    }
+}
 
-        queries.push(Query { left, right, k });
+/// Tokens of one input line, with its number for error messages.
+struct Line<'a> {
+    number: usize,
+    tokens: SplitWhitespace<'a>,
+}
 
-        // Queries may refer to positions beyond the last segment endpoint.
-        if right > max_coord {
-            max_coord = right;
+impl<'a> Line<'a> {
+    /// Splits `input` into its non-blank lines.
+    fn split(input: &'a str) -> impl Iterator<Item = Line<'a>> {
+        input
+            .lines()
+            .enumerate()
+            .map(|(index, line)| Line {
+                number: index + 1,
+                tokens: line.split_whitespace(),
+            })
+            .filter(|line| line.tokens.clone().next().is_some())
+    }
+
+    /// Parses the next token of the line.
+    fn value<T: FromStr>(&mut self) -> Result<T, InputError> {
+        self.optional()?.ok_or(InputError::UnexpectedEof)
+    }
+
+    /// Parses the next token of the line, or returns `None` if there is none.
+    fn optional<T: FromStr>(&mut self) -> Result<Option<T>, InputError> {
+        self.tokens
+            .next()
+            .map(|token| {
+                token.parse().map_err(|_| InputError::InvalidNumber {
+                    line: self.number,
+                    token: token.to_string(),
+                })
+            })
+            .transpose()
+    }
+
+    /// Checks that every token of the line was read.
+    fn finish(mut self) -> Result<(), InputError> {
+        match self.tokens.next() {
+            Some(token) => Err(InputError::TrailingToken {
+                line: self.number,
+                token: token.to_string(),
+            }),
+            None => Ok(()),
         }
     }
+}
 
-    // Edge case: if there is nothing at all, return empty string
-    if max_coord == 0 && n == 0 && m == 0 {
-        return String::new();
+/// Solves a dynamic IsThere stream, where segments are added and removed
+/// between the queries, reading every range as closed (`[L, R]`).
+///
+/// # Panics
+/// Panics with the [`InputError`] message if the stream is invalid.
+pub fn solve_dynamic(input: &str) -> String {
+    try_solve_dynamic(input, Convention::Closed)
+        .unwrap_or_else(|err| panic!("Invalid dynamic IsThere input: {err}"))
+}
+
+/// Solves a dynamic IsThere stream, reading the ranges with the given
+/// `convention`.
+///
+/// The input starts with the number of operations `q`, followed by `q`
+/// operations, one per line:
+///
+/// * `add L R` → a segment `[L, R]` starts covering its positions
+/// * `remove L R` → one previously added `[L, R]` stops covering them
+/// * `isthere i j k` → prints `1` if some `x` in `[i, j]` is covered by
+///   exactly `k` of the current segments, `0` otherwise
+///
+/// Coordinates are signed, as in [`try_solve_with`]. All bounds are read up
+/// front, shifted and compressed; the coverage lives in a lazy min/max tree
+/// where `add` / `remove` are range additions of `±1`. Each operation costs
+/// O(log q) plus the pruned search of `isthere`.
+///
+/// A [`RangeAddFenwick`] receives the same additions, so an `isthere` within
+/// a single elementary interval (e.g. `isthere x x k`) reads its coverage
+/// with one prefix sum instead of searching the tree.
+pub fn try_solve_dynamic(input: &str, convention: Convention) -> Result<String, InputError> {
+    let operations = read_operations(input)?;
+
+    // Closed ranges in input coordinates, `None` if empty
+    let closed: Vec<Option<(i128, i128)>> = operations
+        .iter()
+        .map(|&(_, left, right)| convention.last(left, right).map(|right| (left, right)))
+        .collect();
+    let shifted = shifter(span(closed.iter().flatten().copied()));
+    let ranges: Vec<Option<(u64, u64)>> = closed
+        .iter()
+        .map(|range| {
+            range
+                .map(|(left, right)| Ok((shifted(left)?, shifted(right)?)))
+                .transpose()
+        })
+        .collect::<Result<_, _>>()?;
+
+    let points = breakpoints(ranges.iter().flatten().copied());
+    let mut seg_tree: LazySegTree<MinMax, Add> =
+        LazySegTree::new(&vec![MinMaxValue::from(0); points.len()]);
+    let mut coverage = RangeAddFenwick::new(points.len());
+    // Added segments are matched by their bounds as written in the input
+    let mut active: HashMap<(i128, i128), usize> = HashMap::new();
+
+    let mut result_lines = Vec::new();
+    for (index, (&(operation, left, right), range)) in operations.iter().zip(ranges).enumerate() {
+        let intervals = range.map(|(left, right)| interval_range(&points, left, right));
+        match operation {
+            Operation::Add => {
+                *active.entry((left, right)).or_default() += 1;
+                if let Some(intervals) = intervals {
+                    coverage.add(intervals.clone(), 1);
+                    seg_tree.apply(intervals, &1);
+                }
+            }
+            Operation::Remove => {
+                let count = active
+                    .get_mut(&(left, right))
+                    .filter(|count| **count > 0)
+                    .ok_or(InputError::NotAdded { index, left, right })?;
+                *count -= 1;
+                if let Some(intervals) = intervals {
+                    coverage.add(intervals.clone(), -1);
+                    seg_tree.apply(intervals, &-1);
+                }
+            }
+            Operation::IsThere { k } => {
+                let exists = intervals.is_some_and(|intervals| {
+                    if intervals.len() == 1 {
+                        // Coverage is constant inside an elementary interval
+                        coverage.get(intervals.start) == k
+                    } else {
+                        seg_tree
+                            .find_first(intervals, |node| node.min <= k && k <= node.max)
+                            .is_some()
+                    }
+                });
+                result_lines.push(if exists { "1" } else { "0" });
+            }
+        }
     }
 
+    Ok(result_lines.join("\n"))
+}
+
+/// Reads the operations of a dynamic stream with their bounds `(L, R)` as
+/// written in the input.
+fn read_operations(input: &str) -> Result<Vec<(Operation, i128, i128)>, InputError> {
+    let mut lines = Line::split(input);
+
+    let Some(mut header) = lines.next() else {
+        return Ok(Vec::new());
+    };
+    let q: usize = header.value()?;
+    header.finish()?;
+
+    let mut operations = Vec::with_capacity(q);
+    for index in 0..q {
+        let mut line = lines.next().ok_or(InputError::UnexpectedEof)?;
+        let name: String = line.value()?;
+        if !matches!(name.as_str(), "add" | "remove" | "isthere") {
+            return Err(InputError::UnknownOperation {
+                line: line.number,
+                name,
+            });
+        }
+        let left = line.value()?;
+        let right = line.value()?;
+        let operation = match name.as_str() {
+            "add" => Operation::Add,
+            "remove" => Operation::Remove,
+            _ => Operation::IsThere { k: line.value()? },
+        };
+        line.finish()?;
+        if left > right {
+            return Err(InputError::InvalidOperation {
+                index,
+                name,
+                left,
+                right,
+            });
+        }
+        operations.push((operation, left, right));
+    }
+    Ok(operations)
+}
+
+/// Parses the next whitespace-separated token.
+pub(crate) fn next_value<T: FromStr>(iterator: &mut SplitWhitespace<'_>) -> T
+where
+    T::Err: fmt::Debug,
+{
+    iterator
+        .next()
+        .expect("Unexpected end of input")
+        .parse()
+        .expect("Invalid number in input")
+}
+
+/// Answers the queries with a difference array over `0..=max_coord` and a
+/// min/max segment tree on the resulting coverage.
+fn solve_dense(segments: &[Segment], queries: &[Query], max_coord: usize) -> Vec<bool> {
     // Build difference array for coverage
     let len = max_coord + 1;
     let mut diff = vec![0_i64; len + 1];
 
-    for seg in &segments {
-        diff[seg.left] += 1;
-        if seg.right + 1 < len {
-            diff[seg.right + 1] -= 1;
+    for seg in segments {
+        diff[seg.left as usize] = diff[seg.left as usize].wrapping_add(seg.weight);
+        if (seg.right as usize) + 1 < len {
+            diff[seg.right as usize + 1] = diff[seg.right as usize + 1].wrapping_sub(seg.weight);
         }
     }
 
-    // Build coverage array via prefix sums
+    // Build coverage array via prefix sums: a difference may wrap, but every
+    // coverage fits in i64 (see check_weights), so the sums are exact
     let mut coverage = vec![0_i64; len];
     let mut running = 0_i64;
     for x in 0..len {
-        running += diff[x];
+        running = running.wrapping_add(diff[x]);
         coverage[x] = running;
     }
 
-    // Build segment tree on coverage
-    let seg_tree = SegmentTree::new(&coverage);
+    // Build min/max segment tree on coverage
+    let mut seg_tree: LazySegTree<MinMax, Add> =
+        coverage.iter().copied().map(MinMaxValue::from).collect();
 
     // Answer queries in order
-    let mut result_lines = Vec::with_capacity(m);
-
-    for query in queries {
-        let exists = seg_tree.exists_value_in_range(query.left, query.right, query.k);
-        if exists {
-            result_lines.push("1".to_string());
-        } else {
-            result_lines.push("0".to_string());
-        }
+    queries
+        .iter()
+        .map(|query| {
+            exists_value_in_range(
+                &mut seg_tree,
+                query.left as usize,
+                query.right as usize,
+                query.k,
+            )
+        })
+        .collect()
+}
+
+/// Answers the queries with a sparse segment tree holding the coverage of
+/// every coordinate up to `u64::MAX`; each segment is a range add.
+fn solve_sparse(segments: &[Segment], queries: &[Query]) -> Vec<bool> {
+    let mut seg_tree = SparseSegTree::new();
+    for seg in segments {
+        seg_tree.add(seg.left..=seg.right, seg.weight);
     }
 
-    result_lines.join("\n")
+    queries
+        .iter()
+        .map(|query| {
+            let k = query.k;
+            seg_tree
+                .find_first(query.left..=query.right, |node| {
+                    node.min <= k && k <= node.max
+                })
+                .is_some()
+        })
+        .collect()
 }
 
-#[derive(Debug, Clone, Copy)]
-struct Segment {
-    left: usize,
-    right: usize,
+/// Answers the queries with a min/max tree over the compressed coverage, on
+/// the tree backend `B`.
+fn solve_compressed<B: TreeBackend>(segments: &[Segment], queries: &[Query]) -> Vec<bool> {
+    let (coverage, ranges) = compress(segments, queries);
+
+    // Build min/max segment tree on the interval coverage
+    let mut seg_tree: B::Tree<MinMax, Add> =
+        coverage.iter().copied().map(MinMaxValue::from).collect();
+
+    queries
+        .iter()
+        .zip(ranges)
+        .map(|(query, range)| {
+            let k = query.k;
+            seg_tree
+                .find_first(range, |node| node.min <= k && k <= node.max)
+                .is_some()
+        })
+        .collect()
 }
 
-#[derive(Debug, Clone, Copy)]
-struct Query {
-    left: usize,
-    right: usize,
-    k: i64,
+/// Answers the queries with a min/max sparse table over the compressed
+/// coverage: the coverage never changes after construction.
+fn solve_sparse_table(segments: &[Segment], queries: &[Query]) -> Vec<bool> {
+    let (coverage, ranges) = compress(segments, queries);
+    let table: SparseTable<MinMax> = coverage.iter().copied().map(MinMaxValue::from).collect();
+
+    queries
+        .iter()
+        .zip(ranges)
+        .map(|(query, range)| {
+            let k = query.k;
+            table
+                .find_first(range, |node| node.min <= k && k <= node.max)
+                .is_some()
+        })
+        .collect()
 }
 
-#[derive(Debug, Clone, Copy)]
-struct MinMaxNode {
-    min_value: i64,
-    max_value: i64,
+/// Answers the queries with a [`CoverageIndex`]: a query is a single binary
+/// search in the list of intervals with coverage `k`, so it costs
+/// O(log n) whatever the coverage pattern.
+fn solve_position_lists(segments: &[Segment], queries: &[Query]) -> Vec<bool> {
+    let index: CoverageIndex = segments
+        .iter()
+        .map(|seg| (seg.left..=seg.right, seg.weight))
+        .collect();
+
+    queries
+        .iter()
+        .map(|query| {
+            index
+                .first_with(query.left..=query.right, query.k)
+                .is_some()
+        })
+        .collect()
 }
 
-impl MinMaxNode {
-    fn from_single(value: i64) -> Self {
-        Self {
-            min_value: value,
-            max_value: value,
-        }
-    }
-
-    fn empty() -> Self {
-        Self {
-            min_value: i64::MAX,
-            max_value: i64::MIN,
-        }
-    }
-
-    fn merge(left: MinMaxNode, right: MinMaxNode) -> MinMaxNode {
-        MinMaxNode {
-            min_value: left.min_value.min(right.min_value),
-            max_value: left.max_value.max(right.max_value),
-        }
-    }
+/// Answers the queries offline with Mo's algorithm over the compressed
+/// coverage: the window keeps the number of intervals holding each value.
+fn solve_mo(segments: &[Segment], queries: &[Query]) -> Vec<bool> {
+    let (coverage, ranges) = compress(segments, queries);
+    let mut window = CoverageWindow {
+        coverage: &coverage,
+        queries,
+        counts: HashMap::new(),
+    };
+    mo::process(&mut window, coverage.len(), &ranges, Order::Hilbert)
 }
 
-struct SegmentTree {
-    n: usize,
-    tree: Vec<MinMaxNode>,
+/// Window of [`solve_mo`]: how many intervals in it hold each coverage value.
+struct CoverageWindow<'a> {
+    coverage: &'a [i64],
+    queries: &'a [Query],
+    counts: HashMap<i64, usize>,
 }
 
-impl SegmentTree {
-    fn new(values: &[i64]) -> Self {
-        let n = values.len();
-        let size = 4 * n.max(1);
-        let mut st = Self {
-            n,
-            tree: vec![MinMaxNode::empty(); size],
-        };
-
-        if n > 0 {
-            st.build(1, 0, n - 1, values);
-        }
+impl MoState for CoverageWindow<'_> {
+    type Answer = bool;
 
-        st
+    fn add(&mut self, index: usize) {
+        *self.counts.entry(self.coverage[index]).or_default() += 1;
     }
 
-    fn build(&mut self, index: usize, left: usize, right: usize, values: &[i64]) {
-        if left == right {
-            self.tree[index] = MinMaxNode::from_single(values[left]);
-            return;
-        }
+    fn remove(&mut self, index: usize) {
+        let count = self
+            .counts
+            .get_mut(&self.coverage[index])
+            .expect("Removed value is in the window");
+        *count -= 1;
+    }
 
-        let mid = (left + right) / 2;
-        let left_child = index * 2;
-        let right_child = left_child + 1;
+    fn answer(&self, query: usize) -> bool {
+        self.counts
+            .get(&self.queries[query].k)
+            .is_some_and(|&count| count > 0)
+    }
+}
 
-        self.build(left_child, left, mid, values);
-        self.build(right_child, mid + 1, right, values);
+/// Compresses the coordinates into the elementary intervals delimited by
+/// every `L` and `R + 1` of the segments and queries: coverage is constant
+/// inside each of them, and every query range is a run of consecutive ones.
+///
+/// Returns the coverage of each interval and the half-open range of
+/// intervals spanned by each query.
+fn compress(segments: &[Segment], queries: &[Query]) -> (Vec<i64>, Vec<Range<usize>>) {
+    let points = breakpoints(
+        segments
+            .iter()
+            .map(|seg| (seg.left, seg.right))
+            .chain(queries.iter().map(|query| (query.left, query.right))),
+    );
+    let intervals = |left, right| interval_range(&points, left, right);
+
+    let coverage = interval_coverage(
+        &points,
+        segments.iter().map(|seg| (seg.left, seg.right, seg.weight)),
+    )
+    .expect("Weights were checked by parse_input");
+    let ranges = queries
+        .iter()
+        .map(|query| intervals(query.left, query.right))
+        .collect();
+    (coverage, ranges)
+}
 
-        self.tree[index] = MinMaxNode::merge(self.tree[left_child], self.tree[right_child]);
-    }
+/// Sorted, deduplicated `L` and `R + 1` of every inclusive `[L, R]` bound:
+/// interval `t` spans `[points[t], points[t + 1] - 1]` (the last one up to
+/// `u64::MAX`).
+pub(crate) fn breakpoints<I: IntoIterator<Item = (u64, u64)>>(bounds: I) -> Vec<u64> {
+    let mut points: Vec<u64> = bounds
+        .into_iter()
+        .flat_map(|(left, right)| [Some(left), right.checked_add(1)])
+        .flatten()
+        .collect();
+    points.sort_unstable();
+    points.dedup();
+    points
+}
 
-    /// Returns true if there exists an index x in [ql, qr]
-    /// such that coverage[x] == k.
-    fn exists_value_in_range(&self, ql: usize, qr: usize, k: i64) -> bool {
-        if self.n == 0 {
-            return false;
-        }
-        self.exists_value_in_range_rec(1, 0, self.n - 1, ql, qr, k)
+/// Total weight of the `(L, R, W)` segments covering each interval of
+/// `points`, which must hold every `L` and `R + 1` (see [`breakpoints`]), or
+/// the first position whose total does not fit in `i64`.
+pub(crate) fn interval_coverage<I>(points: &[u64], segments: I) -> Result<Vec<i64>, u64>
+where
+    I: IntoIterator<Item = (u64, u64, i64)>,
+{
+    // Build difference array for coverage over the intervals, in i128 so
+    // that only the totals have to fit
+    let mut diff = vec![0_i128; points.len() + 1];
+    for (left, right, weight) in segments {
+        let range = interval_range(points, left, right);
+        diff[range.start] += i128::from(weight);
+        diff[range.end] -= i128::from(weight);
     }
 
-    fn exists_value_in_range_rec(
-        &self,
-        index: usize,
-        left: usize,
-        right: usize,
-        ql: usize,
-        qr: usize,
-        k: i64,
-    ) -> bool {
-        // No overlap
-        if right < ql || qr < left {
-            return false;
-        }
-
-        let node = self.tree[index];
+    let mut running = 0_i128;
+    diff[..points.len()]
+        .iter()
+        .zip(points)
+        .map(|(&delta, &point)| {
+            running += delta;
+            i64::try_from(running).map_err(|_| point)
+        })
+        .collect()
+}
 
-        // Prune by min/max range: if k is outside [min_value, max_value],
-        // it cannot exist in this segment.
-        if k < node.min_value || k > node.max_value {
-            return false;
-        }
+/// Half-open range of interval indices covering `[left, right]`, whose
+/// bounds must have been passed to [`breakpoints`].
+pub(crate) fn interval_range(points: &[u64], left: u64, right: u64) -> Range<usize> {
+    let start = points
+        .binary_search(&left)
+        .expect("Left bound is a breakpoint");
+    let end = right.checked_add(1).map_or(points.len(), |next| {
+        points
+            .binary_search(&next)
+            .expect("Right bound + 1 is a breakpoint")
+    });
+    start..end
+}
 
-        // Leaf node: single position
-        if left == right {
-            // Here min_value == max_value == coverage[left]
-            return node.min_value == k;
-        }
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    left: u64,
+    right: u64,
+    weight: i64,
+}
 
-        // Partial or full overlap, not a leaf: explore children
-        let mid = (left + right) / 2;
-        let left_child = index * 2;
-        let right_child = left_child + 1;
+#[derive(Debug, Clone, Copy)]
+struct Query {
+    left: u64,
+    right: u64,
+    k: i64,
+}
 
-        // Check left child first; if found, no need to check right.
-        if self.exists_value_in_range_rec(left_child, left, mid, ql, qr, k) {
-            return true;
-        }
+/// Kind of a [`solve_dynamic`] operation.
+#[derive(Debug, Clone, Copy)]
+enum Operation {
+    Add,
+    Remove,
+    IsThere { k: i64 },
+}
 
-        // Otherwise, check right child.
-        self.exists_value_in_range_rec(right_child, mid + 1, right, ql, qr, k)
-    }
+/// Returns true if there exists an index x in [ql, qr]
+/// such that coverage[x] == k.
+fn exists_value_in_range(
+    seg_tree: &mut LazySegTree<MinMax, Add>,
+    ql: usize,
+    qr: usize,
+    k: i64,
+) -> bool {
+    // Prune by min/max range: if k is outside [min, max],
+    // it cannot exist in this segment.
+    seg_tree
+        .find_first(ql..qr + 1, |node| node.min <= k && k <= node.max)
+        .is_some()
 }
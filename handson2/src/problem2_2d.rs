@@ -0,0 +1,212 @@
+//! IsThere on a grid: axis-aligned rectangles instead of segments.
+//!
+//! Each rectangle covers the cells `(x, y)` with `x1 ≤ x ≤ x2` and
+//! `y1 ≤ y ≤ y2`, and a query asks whether some cell of a query rectangle is
+//! covered by exactly `k` rectangles. The input mirrors [`crate::problem2`]:
+//!
+//! ```text
+//! n m
+//! x1 y1 x2 y2        (n rectangles)
+//! x1 y1 x2 y2 k      (m queries)
+//! ```
+
+use std::ops::Range;
+use std::str::SplitWhitespace;
+
+use crate::lazy_segtree::{Add, LazySegTree, MinMax, MinMaxValue};
+use crate::problem2::{breakpoints, interval_range, next_value};
+
+/// Strategy used by [`solve_with`]. Both backends give identical answers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Backend {
+    /// 2D difference array over every cell of `0..=max_x × 0..=max_y`, with a
+    /// min/max tree per row: O(max_x · max_y) memory, for small grids.
+    Grid,
+    /// Sweep line over the compressed x coordinates, keeping the coverage of
+    /// the compressed y intervals in a min/max tree: O(n + m) memory.
+    #[default]
+    Sweep,
+}
+
+/// Solves a rectangle IsThere input with the default [`Backend`].
+///
+/// # Examples
+/// ```
+/// use handson2::problem2_2d::solve;
+///
+/// // Two overlapping squares: the cells (2..=3, 2..=3) are covered twice
+/// let input = "2 3\n0 0 3 3\n2 2 5 5\n0 0 1 1 2\n1 1 2 2 2\n4 0 5 1 0";
+/// assert_eq!(solve(input), "0\n1\n1");
+/// ```
+pub fn solve(input: &str) -> String {
+    solve_with(input, Backend::default())
+}
+
+/// Solves a rectangle IsThere input with the given `backend`.
+///
+/// # Panics
+/// Panics if the input is malformed or a rectangle has `x1 > x2` or
+/// `y1 > y2`.
+pub fn solve_with(input: &str, backend: Backend) -> String {
+    let (rects, queries) = parse_input(input);
+
+    let answers = match backend {
+        Backend::Grid => solve_grid(&rects, &queries),
+        Backend::Sweep => solve_sweep(&rects, &queries),
+    };
+
+    answers
+        .into_iter()
+        .map(|exists| if exists { "1" } else { "0" })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Rect {
+    x1: u64,
+    y1: u64,
+    x2: u64,
+    y2: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Query {
+    rect: Rect,
+    k: i64,
+}
+
+/// Reads the `n` rectangles and `m` queries of a rectangle IsThere input.
+fn parse_input(input: &str) -> (Vec<Rect>, Vec<Query>) {
+    let mut iterator = input.split_whitespace();
+
+    // n = number of rectangles, m = number of queries
+    let n: usize = iterator.next().map_or(0, |s| s.parse().unwrap());
+    let m: usize = iterator.next().map_or(0, |s| s.parse().unwrap());
+
+    let rects: Vec<Rect> = (0..n).map(|_| read_rect(&mut iterator)).collect();
+    let queries = (0..m)
+        .map(|_| Query {
+            rect: read_rect(&mut iterator),
+            k: next_value(&mut iterator),
+        })
+        .collect();
+
+    (rects, queries)
+}
+
+/// Reads the corners `x1 y1 x2 y2` of a rectangle.
+fn read_rect(iterator: &mut SplitWhitespace<'_>) -> Rect {
+    let rect = Rect {
+        x1: next_value(iterator),
+        y1: next_value(iterator),
+        x2: next_value(iterator),
+        y2: next_value(iterator),
+    };
+    assert!(
+        rect.x1 <= rect.x2 && rect.y1 <= rect.y2,
+        "Rectangle {rect:?} is empty"
+    );
+    rect
+}
+
+/// Answers the queries with a 2D difference array over every cell and a
+/// min/max tree per row of the resulting coverage.
+fn solve_grid(rects: &[Rect], queries: &[Query]) -> Vec<bool> {
+    // Queries may refer to cells beyond the last rectangle
+    let all = rects.iter().chain(queries.iter().map(|query| &query.rect));
+    let width = all
+        .clone()
+        .map(|rect| rect.x2 as usize + 1)
+        .max()
+        .unwrap_or(0);
+    let height = all.map(|rect| rect.y2 as usize + 1).max().unwrap_or(0);
+
+    // Build 2D difference array: +1 at both corners of the main diagonal,
+    // -1 at the other two, one past the rectangle
+    let mut diff = vec![vec![0_i64; width + 1]; height + 1];
+    for rect in rects {
+        let (x1, y1) = (rect.x1 as usize, rect.y1 as usize);
+        let (x2, y2) = (rect.x2 as usize + 1, rect.y2 as usize + 1);
+        diff[y1][x1] += 1;
+        diff[y1][x2] -= 1;
+        diff[y2][x1] -= 1;
+        diff[y2][x2] += 1;
+    }
+
+    // Build coverage via 2D prefix sums, one min/max tree per row
+    let mut above = vec![0_i64; width];
+    let mut rows: Vec<LazySegTree<MinMax, Add>> = Vec::with_capacity(height);
+    for diff_row in &diff[..height] {
+        let mut running = 0_i64;
+        for (x, cell) in above.iter_mut().enumerate() {
+            running += diff_row[x];
+            *cell += running;
+        }
+        rows.push(above.iter().copied().map(MinMaxValue::from).collect());
+    }
+
+    queries
+        .iter()
+        .map(|query| {
+            let Query { rect, k } = *query;
+            let columns = rect.x1 as usize..rect.x2 as usize + 1;
+            rows[rect.y1 as usize..=rect.y2 as usize]
+                .iter_mut()
+                .any(|row| {
+                    row.find_first(columns.clone(), |node| node.min <= k && k <= node.max)
+                        .is_some()
+                })
+        })
+        .collect()
+}
+
+/// Answers the queries offline by sweeping over the elementary x intervals.
+///
+/// Coverage is constant on every cell of the compressed grid, so each query
+/// is answered by searching its y intervals in the tree of every x interval
+/// it spans, until one matches. A query costs O(log n) per x interval
+/// visited (plus the pruned search), and drops out as soon as it matches.
+fn solve_sweep(rects: &[Rect], queries: &[Query]) -> Vec<bool> {
+    let all = || rects.iter().chain(queries.iter().map(|query| &query.rect));
+    let xs = breakpoints(all().map(|rect| (rect.x1, rect.x2)));
+    let ys = breakpoints(all().map(|rect| (rect.y1, rect.y2)));
+    let columns = |rect: &Rect| interval_range(&xs, rect.x1, rect.x2);
+    let rows = |rect: &Rect| interval_range(&ys, rect.y1, rect.y2);
+
+    // Rectangles enter and leave the sweep as range adds on the y intervals
+    let mut events: Vec<Vec<(Range<usize>, i64)>> = vec![Vec::new(); xs.len() + 1];
+    for rect in rects {
+        let span = columns(rect);
+        events[span.start].push((rows(rect), 1));
+        events[span.end].push((rows(rect), -1));
+    }
+    let mut starts: Vec<Vec<usize>> = vec![Vec::new(); xs.len()];
+    for (id, query) in queries.iter().enumerate() {
+        starts[columns(&query.rect).start].push(id);
+    }
+
+    let mut seg_tree: LazySegTree<MinMax, Add> =
+        LazySegTree::new(&vec![MinMaxValue::from(0); ys.len()]);
+    let mut answers = vec![false; queries.len()];
+    let mut active: Vec<usize> = Vec::new();
+    for t in 0..xs.len() {
+        for (range, delta) in &events[t] {
+            seg_tree.apply(range.clone(), delta);
+        }
+
+        // Keep the queries still spanning this x interval and not yet matched
+        active.extend_from_slice(&starts[t]);
+        active.retain(|&id| {
+            let Query { rect, k } = queries[id];
+            if columns(&rect).end <= t {
+                return false;
+            }
+            answers[id] = seg_tree
+                .find_first(rows(&rect), |node| node.min <= k && k <= node.max)
+                .is_some();
+            !answers[id]
+        });
+    }
+    answers
+}
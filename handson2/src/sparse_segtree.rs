@@ -0,0 +1,263 @@
+//! Dynamic (sparse) segment tree over the whole `u64` coordinate space.
+//!
+//! Nodes are allocated only when an update splits them, so memory grows with
+//! the number of updates (O(log U) nodes each, with U = 2⁶⁴) instead of with
+//! the largest coordinate. A missing child stands for a range whose elements
+//! all hold the initial value `0`.
+
+use std::ops::RangeInclusive;
+
+use crate::lazy_segtree::{MinMax, MinMaxValue, Monoid};
+
+/// Index of the root in the arena; also used as the "no child" marker, since
+/// the root is never anyone's child.
+const ROOT: usize = 0;
+
+#[derive(Clone, Copy, Debug)]
+struct Node {
+    /// Min/max of the subtree, including this node's own `add`.
+    value: MinMaxValue,
+    /// Addition applied to the whole subtree and never pushed down.
+    add: i64,
+    left: usize,
+    right: usize,
+}
+
+impl Node {
+    /// A node whose range holds only zeros.
+    fn zero() -> Self {
+        Self {
+            value: MinMaxValue::from(0),
+            add: 0,
+            left: ROOT,
+            right: ROOT,
+        }
+    }
+}
+
+/// Sparse segment tree over the coordinates `0..=u64::MAX`, all initially `0`,
+/// with range add and range min/max queries.
+///
+/// Ranges are inclusive (`start..=end`) so that `u64::MAX` itself can be
+/// addressed; a range with `start > end` is empty. Every operation runs in
+/// O(log U) = O(64) node visits. Additions stay on the nodes they cover
+/// instead of being pushed down, so queries never allocate.
+///
+/// # Examples
+/// ```
+/// use handson2::sparse_segtree::SparseSegTree;
+///
+/// let mut tree = SparseSegTree::new();
+/// tree.add(10..=1_000_000_000_000, 2);
+/// tree.add(u64::MAX - 1..=u64::MAX, -1);
+/// assert_eq!(tree.max(0..=u64::MAX), Some(2));
+/// assert_eq!(tree.min(0..=u64::MAX), Some(-1));
+/// assert_eq!(tree.find_first(0..=u64::MAX, |v| v.min <= 2 && 2 <= v.max), Some(10));
+/// ```
+#[derive(Clone, Debug)]
+pub struct SparseSegTree {
+    nodes: Vec<Node>,
+}
+
+impl SparseSegTree {
+    /// Creates a tree where every coordinate holds `0`.
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![Node::zero()],
+        }
+    }
+
+    /// Returns the number of allocated nodes.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Applies `A[x] = A[x] + value` for every `x` in `range`.
+    pub fn add(&mut self, range: RangeInclusive<u64>, value: i64) {
+        let (ql, qr) = range.into_inner();
+        if ql <= qr {
+            self.add_rec(ROOT, 0, u64::MAX, ql, qr, value);
+        }
+    }
+
+    /// Returns the minimum and maximum of `range`, or `None` if it is empty.
+    pub fn query(&self, range: RangeInclusive<u64>) -> Option<MinMaxValue> {
+        let (ql, qr) = range.into_inner();
+        (ql <= qr).then(|| self.query_rec(Some(ROOT), 0, u64::MAX, ql, qr))
+    }
+
+    /// Returns the minimum of `range`, or `None` if it is empty.
+    pub fn min(&self, range: RangeInclusive<u64>) -> Option<i64> {
+        self.query(range).map(|value| value.min)
+    }
+
+    /// Returns the maximum of `range`, or `None` if it is empty.
+    pub fn max(&self, range: RangeInclusive<u64>) -> Option<i64> {
+        self.query(range).map(|value| value.max)
+    }
+
+    /// Returns the leftmost coordinate in `range` whose value satisfies `pred`.
+    ///
+    /// `pred` is also used to prune whole nodes, so it must hold for the
+    /// min/max of every node containing an element that satisfies it.
+    pub fn find_first<P>(&self, range: RangeInclusive<u64>, pred: P) -> Option<u64>
+    where
+        P: Fn(&MinMaxValue) -> bool,
+    {
+        let (ql, qr) = range.into_inner();
+        if ql > qr {
+            return None;
+        }
+        self.find_first_rec(Some(ROOT), 0, u64::MAX, (ql, qr), 0, &pred)
+    }
+
+    /// Returns the child index, or `None` for a missing (all-zero) child.
+    fn child(index: usize) -> Option<usize> {
+        (index != ROOT).then_some(index)
+    }
+
+    /// Min/max of an optional node (zeros if missing).
+    fn value(&self, node: Option<usize>) -> MinMaxValue {
+        node.map_or(MinMaxValue::from(0), |index| self.nodes[index].value)
+    }
+
+    /// Recomputes a node from its children and its own addition.
+    fn pull(&mut self, index: usize) {
+        let node = self.nodes[index];
+        let left = self.value(Self::child(node.left));
+        let right = self.value(Self::child(node.right));
+        let value = MinMax::combine(&left, &right);
+        self.nodes[index].value = MinMaxValue {
+            min: value.min + node.add,
+            max: value.max + node.add,
+        };
+    }
+
+    fn add_rec(&mut self, index: usize, left: u64, right: u64, ql: u64, qr: u64, value: i64) {
+        // Fully covered: keep the addition on this node
+        if ql <= left && right <= qr {
+            let node = &mut self.nodes[index];
+            node.add += value;
+            node.value.min += value;
+            node.value.max += value;
+            return;
+        }
+
+        // Partial overlap: allocate the children that are touched and recurse
+        let mid = left + (right - left) / 2;
+        if ql <= mid {
+            let child = self.child_or_alloc(index, true);
+            self.add_rec(child, left, mid, ql, qr, value);
+        }
+        if mid < qr {
+            let child = self.child_or_alloc(index, false);
+            self.add_rec(child, mid + 1, right, ql, qr, value);
+        }
+        self.pull(index);
+    }
+
+    /// Returns the left or right child of `index`, allocating it if missing.
+    fn child_or_alloc(&mut self, index: usize, is_left: bool) -> usize {
+        let node = self.nodes[index];
+        let child = if is_left { node.left } else { node.right };
+        if let Some(child) = Self::child(child) {
+            return child;
+        }
+
+        self.nodes.push(Node::zero());
+        let child = self.nodes.len() - 1;
+        if is_left {
+            self.nodes[index].left = child;
+        } else {
+            self.nodes[index].right = child;
+        }
+        child
+    }
+
+    /// Min/max of `[ql, qr] ∩ [left, right]`, relative to the additions of
+    /// the ancestors of `node`.
+    fn query_rec(
+        &self,
+        node: Option<usize>,
+        left: u64,
+        right: u64,
+        ql: u64,
+        qr: u64,
+    ) -> MinMaxValue {
+        // Missing node: all zeros
+        let Some(index) = node else {
+            return MinMaxValue::from(0);
+        };
+        // Fully covered
+        let node = self.nodes[index];
+        if ql <= left && right <= qr {
+            return node.value;
+        }
+
+        // Partial overlap
+        let mid = left + (right - left) / 2;
+        let mut value = MinMax::identity();
+        for (child, left, right) in [(node.left, left, mid), (node.right, mid + 1, right)] {
+            if ql <= right && left <= qr {
+                let child = self.query_rec(Self::child(child), left, right, ql, qr);
+                value = MinMax::combine(&value, &child);
+            }
+        }
+        value.min += node.add;
+        value.max += node.add;
+        value
+    }
+
+    /// Leftmost coordinate of `[ql, qr] ∩ [left, right]` satisfying `pred`,
+    /// where `offset` is the sum of the additions of the ancestors of `node`.
+    fn find_first_rec<P>(
+        &self,
+        node: Option<usize>,
+        left: u64,
+        right: u64,
+        (ql, qr): (u64, u64),
+        offset: i64,
+        pred: &P,
+    ) -> Option<u64>
+    where
+        P: Fn(&MinMaxValue) -> bool,
+    {
+        // No overlap
+        if right < ql || qr < left {
+            return None;
+        }
+
+        // Prune by the node aggregate
+        let value = self.value(node);
+        let value = MinMaxValue {
+            min: value.min + offset,
+            max: value.max + offset,
+        };
+        if !pred(&value) {
+            return None;
+        }
+
+        // A missing node is uniform, and so is a leaf: the first position wins
+        let Some(index) = node else {
+            return Some(left.max(ql));
+        };
+        if left == right {
+            return Some(left);
+        }
+
+        let node = self.nodes[index];
+        let mid = left + (right - left) / 2;
+        let offset = offset + node.add;
+        let query = (ql, qr);
+        self.find_first_rec(Self::child(node.left), left, mid, query, offset, pred)
+            .or_else(|| {
+                self.find_first_rec(Self::child(node.right), mid + 1, right, query, offset, pred)
+            })
+    }
+}
+
+impl Default for SparseSegTree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
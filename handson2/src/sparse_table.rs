@@ -0,0 +1,180 @@
+//! Sparse table for static range queries with an idempotent aggregate.
+//!
+//! Level `j` stores the aggregate of every range of length `2^j`. Any range
+//! is the union of two (possibly overlapping) such ranges, and overlapping
+//! does not matter when `combine(x, x) == x`: min, max and gcd answer in
+//! O(1) after an O(n log n) build. The array cannot be updated.
+
+use std::fmt;
+use std::ops::Range;
+
+use crate::lazy_segtree::{Element, Max, Min, MinMax, Monoid};
+
+/// A [`Monoid`] whose `combine(x, x) == x`, so that the aggregates of
+/// overlapping ranges can be combined.
+pub trait Idempotent: Monoid {}
+
+impl<T: Element> Idempotent for Min<T> {}
+impl<T: Element> Idempotent for Max<T> {}
+impl<T: Element> Idempotent for MinMax<T> {}
+impl Idempotent for Gcd {}
+
+/// Greatest common divisor monoid, with `0` as identity.
+pub struct Gcd;
+
+impl Monoid for Gcd {
+    type Value = u64;
+
+    fn identity() -> u64 {
+        0
+    }
+
+    fn combine(left: &u64, right: &u64) -> u64 {
+        let (mut a, mut b) = (*left, *right);
+        while b != 0 {
+            (a, b) = (b, a % b);
+        }
+        a
+    }
+}
+
+/// Static range aggregate over an [`Idempotent`] monoid.
+///
+/// All ranges are 0-based and half-open (`start..end`); an empty range
+/// aggregates to `M::identity()`.
+///
+/// # Panics
+/// Every range method panics if `start > end` or `end > len()`.
+///
+/// # Examples
+/// ```
+/// use handson2::lazy_segtree::Min;
+/// use handson2::sparse_table::{Gcd, SparseTable};
+///
+/// let table: SparseTable<Min> = [5, 2, 8, 6, 3].into_iter().collect();
+/// assert_eq!(table.query(2..5), 3);
+///
+/// let table: SparseTable<Gcd> = [12, 18, 24, 7].into_iter().collect();
+/// assert_eq!(table.query(0..3), 6);
+/// assert_eq!(table.query(0..4), 1);
+/// ```
+pub struct SparseTable<M: Idempotent> {
+    /// `levels[j][i]` aggregates `i..i + 2^j`.
+    levels: Vec<Vec<M::Value>>,
+}
+
+impl<M: Idempotent> SparseTable<M> {
+    /// Builds the table over a copy of `values` in O(n log n).
+    pub fn new(values: &[M::Value]) -> Self {
+        let mut levels = vec![values.to_vec()];
+        let mut width = 1;
+        while 2 * width <= values.len() {
+            let previous = levels.last().expect("Level 0 exists");
+            let level = (0..=values.len() - 2 * width)
+                .map(|i| M::combine(&previous[i], &previous[i + width]))
+                .collect();
+            levels.push(level);
+            width *= 2;
+        }
+        Self { levels }
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    /// Returns `true` if the table holds no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the aggregate of `range` in O(1).
+    pub fn query(&self, range: Range<usize>) -> M::Value {
+        crate::check_range(&range, self.len());
+        if range.is_empty() {
+            return M::identity();
+        }
+
+        // Two blocks of length 2^level covering the range from both ends
+        let level = range.len().ilog2() as usize;
+        let width = 1 << level;
+        M::combine(
+            &self.levels[level][range.start],
+            &self.levels[level][range.end - width],
+        )
+    }
+
+    /// Returns the leftmost index in `range` whose value satisfies `pred`.
+    ///
+    /// `pred` is also used to prune whole subranges, so it must hold for the
+    /// aggregate of every range containing an element that satisfies it.
+    /// The range is split into O(log n) aligned blocks of length `2^j`, read
+    /// directly from `levels[j]`, and a block that passes `pred` is searched
+    /// by halving it: the same blocks a segment tree search would visit.
+    pub fn find_first<P>(&self, range: Range<usize>, pred: P) -> Option<usize>
+    where
+        P: Fn(&M::Value) -> bool,
+    {
+        crate::check_range(&range, self.len());
+
+        let mut start = range.start;
+        while start < range.end {
+            // Largest aligned block starting at `start` and ending in the range
+            let aligned = start.trailing_zeros() as usize;
+            let fits = (range.end - start).ilog2() as usize;
+            let level = aligned.min(fits).min(self.levels.len() - 1);
+
+            if let Some(index) = self.find_first_block(level, start, &pred) {
+                return Some(index);
+            }
+            start += 1 << level;
+        }
+        None
+    }
+
+    /// Searches the block `start..start + 2^level` (aligned to its length).
+    fn find_first_block<P>(&self, level: usize, start: usize, pred: &P) -> Option<usize>
+    where
+        P: Fn(&M::Value) -> bool,
+    {
+        // Pruned by the aggregate
+        if !pred(&self.levels[level][start]) {
+            return None;
+        }
+        // Single element
+        if level == 0 {
+            return Some(start);
+        }
+
+        let half = 1 << (level - 1);
+        self.find_first_block(level - 1, start, pred)
+            .or_else(|| self.find_first_block(level - 1, start + half, pred))
+    }
+}
+
+impl<M: Idempotent> Clone for SparseTable<M> {
+    fn clone(&self) -> Self {
+        Self {
+            levels: self.levels.clone(),
+        }
+    }
+}
+
+impl<M: Idempotent> fmt::Debug for SparseTable<M>
+where
+    M::Value: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SparseTable")
+            .field("levels", &self.levels)
+            .finish()
+    }
+}
+
+impl<M: Idempotent> FromIterator<M::Value> for SparseTable<M> {
+    fn from_iter<I: IntoIterator<Item = M::Value>>(iter: I) -> Self {
+        let values: Vec<M::Value> = iter.into_iter().collect();
+        Self::new(&values)
+    }
+}
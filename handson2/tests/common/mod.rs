@@ -0,0 +1,73 @@
+//! Helpers shared by the integration tests and benches.
+//!
+//! Every test file compiles this module on its own and uses a subset of it.
+#![allow(dead_code)]
+
+use std::ops::Range;
+use std::path::Path;
+
+/// Minimal xorshift generator for reproducible random inputs.
+pub struct XorShift(pub u64);
+
+impl XorShift {
+    pub fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Returns a value in `low..=high`.
+    pub fn range<T: Sample>(&mut self, low: T, high: T) -> T {
+        let span = (high.into_i128() - low.into_i128() + 1) as u64;
+        T::from_i128(low.into_i128() + i128::from(self.next() % span))
+    }
+
+    /// Returns a value in `0..bound`.
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+
+    /// Returns a random, possibly empty, subrange of `0..len`.
+    pub fn subrange(&mut self, len: usize) -> Range<usize> {
+        let start = self.range(0, len);
+        start..self.range(start, len)
+    }
+}
+
+/// Integer types [`XorShift::range`] can draw.
+pub trait Sample: Copy {
+    fn into_i128(self) -> i128;
+    fn from_i128(value: i128) -> Self;
+}
+
+macro_rules! impl_sample {
+    ($($t:ty),*) => {$(
+        impl Sample for $t {
+            fn into_i128(self) -> i128 {
+                self as i128
+            }
+
+            fn from_i128(value: i128) -> Self {
+                value as $t
+            }
+        }
+    )*};
+}
+
+impl_sample!(i32, i64, u64, usize);
+
+/// Normalize txt test file line endings and trailing spaces
+pub fn normalize(s: &str) -> String {
+    s.replace("\r\n", "\n").trim_end().to_string()
+}
+
+/// Loads `tests/data/<problem>/input<i>.txt` and the matching output.
+pub fn load_case(problem: &str, i: usize) -> (String, String) {
+    let read = |name: String| {
+        let path = format!("data/{problem}/{name}{i}.txt");
+        std::fs::read_to_string(Path::new("tests").join(&path))
+            .unwrap_or_else(|_| panic!("Cannot read {path}"))
+    };
+    (read("input".into()), read("output".into()))
+}
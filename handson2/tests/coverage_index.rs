@@ -0,0 +1,196 @@
+mod common;
+
+use std::collections::BTreeMap;
+
+use handson2::coverage_index::{CoverageIndex, Extremum};
+
+use common::XorShift;
+
+#[test]
+fn test_weighted_coverage_index() {
+    let index = CoverageIndex::weighted(&[(0..=u64::MAX, -2), (10..=20, 5), (15..=15, -3)]);
+    assert_eq!(index.coverage_at(0), -2);
+    assert_eq!(index.coverage_at(15), 0);
+    assert_eq!(index.count_with(0..=u64::MAX, 3), 10);
+    assert_eq!(index.first_with(0..=u64::MAX, 0), Some(15));
+    assert_eq!(
+        index
+            .min_coverage(0..=u64::MAX)
+            .map(|min| (min.value, min.count)),
+        Some((-2, (1 << 64) - 11))
+    );
+    assert_eq!(index.length_at_least(0..=u64::MAX, -1), 11);
+}
+
+#[test]
+fn test_intervals_start_at_zero() {
+    let empty = CoverageIndex::new(&[]);
+    assert_eq!(empty.coverage_at(0), 0);
+    assert_eq!(empty.count_with(0..=u64::MAX, 0), 1 << 64);
+
+    // Coverage 0 first occurs at 0 unless a segment starts there
+    for (segments, first_gap, covered) in [([0..=3, 5..=9], 4..=4, 9), ([2..=3, 5..=9], 0..=1, 7)] {
+        let index = CoverageIndex::new(&segments);
+        assert_eq!(index.coverage_at(4), 0);
+        assert_eq!(index.runs_with(0..=u64::MAX, 0).next(), Some(first_gap));
+        assert_eq!(index.count_with(0..=9, 1), covered);
+    }
+}
+
+#[test]
+fn test_extreme_weights() {
+    let index = CoverageIndex::weighted(&[
+        (0..=0, i64::MIN),
+        (1..=1, i64::MAX),
+        (0..=u64::MAX, i64::MAX),
+        (1..=u64::MAX, i64::MIN),
+    ]);
+    assert_eq!(index.coverage_at(0), -1);
+    assert_eq!(index.coverage_at(1), i64::MAX - 1);
+    assert_eq!(index.coverage_at(2), -1);
+}
+
+#[test]
+#[should_panic(expected = "Coverage of position 3 does not fit in i64")]
+fn test_coverage_overflow_panics() {
+    CoverageIndex::weighted(&[(0..=5, i64::MAX), (3..=9, i64::MAX)]);
+}
+
+#[test]
+fn test_witness_api_against_brute_force() {
+    for seed in 1..=30 {
+        let mut rng = XorShift(400 + seed);
+        let segments: Vec<_> = (0..rng.range(0, 8))
+            .map(|_| {
+                let left = rng.range(0, 40);
+                left..=rng.range(left, 40)
+            })
+            .collect();
+        let index = CoverageIndex::new(&segments);
+        let coverage: Vec<i64> = (0..=50)
+            .map(|x| segments.iter().filter(|seg| seg.contains(&x)).count() as i64)
+            .collect();
+
+        for x in 0..=50 {
+            assert_eq!(index.coverage_at(x), coverage[x as usize]);
+        }
+        for _ in 0..50 {
+            let start = rng.range(0, 50);
+            let end = rng.range(start, 50);
+            let k = rng.range(0, 4) as i64;
+            let expected: Vec<u64> = (start..=end)
+                .filter(|&x| coverage[x as usize] == k)
+                .collect();
+
+            assert_eq!(index.first_with(start..=end, k), expected.first().copied());
+            assert_eq!(index.last_with(start..=end, k), expected.last().copied());
+            assert_eq!(index.count_with(start..=end, k), expected.len() as u128);
+            assert_eq!(
+                index.positions_with(start..=end, k).collect::<Vec<_>>(),
+                expected
+            );
+        }
+    }
+}
+
+#[test]
+fn test_witness_api_on_huge_coordinates() {
+    let index = CoverageIndex::new(&[u64::MAX - 9..=u64::MAX, 1 << 62..=1 << 63]);
+    assert_eq!(
+        index.count_with(0..=u64::MAX, 0),
+        (1 << 64) - 10 - (1 << 62) - 1
+    );
+    assert_eq!(index.count_with(0..=u64::MAX, 1), 10 + (1 << 62) + 1);
+    assert_eq!(index.first_with(0..=u64::MAX, 1), Some(1 << 62));
+    assert_eq!(
+        index.runs_with(1 << 63..=u64::MAX, 1).collect::<Vec<_>>(),
+        vec![1 << 63..=1 << 63, u64::MAX - 9..=u64::MAX]
+    );
+    assert_eq!(index.positions_with(u64::MAX - 1..=u64::MAX, 1).count(), 2);
+
+    let (start, end) = (5, 4);
+    assert_eq!(index.count_with(start..=end, 0), 0);
+    assert_eq!(index.first_with(start..=end, 0), None);
+}
+
+#[test]
+fn test_analytics_against_brute_force() {
+    for seed in 1..=30 {
+        let mut rng = XorShift(500 + seed);
+        let segments: Vec<_> = (0..rng.range(0, 8))
+            .map(|_| {
+                let left = rng.range(0, 40);
+                left..=rng.range(left, 40)
+            })
+            .collect();
+        let index = CoverageIndex::new(&segments);
+        let coverage: Vec<i64> = (0..=50)
+            .map(|x| segments.iter().filter(|seg| seg.contains(&x)).count() as i64)
+            .collect();
+
+        for _ in 0..50 {
+            let start = rng.range(0, 50);
+            let end = rng.range(start, 50);
+            let window = &coverage[start as usize..=end as usize];
+            let extremum = |value: i64| {
+                let positions: Vec<u64> = (start..=end)
+                    .filter(|&x| coverage[x as usize] == value)
+                    .collect();
+                Extremum {
+                    value,
+                    first: positions[0],
+                    last: *positions.last().unwrap(),
+                    count: positions.len() as u128,
+                }
+            };
+
+            let max = *window.iter().max().unwrap();
+            let min = *window.iter().min().unwrap();
+            assert_eq!(index.max_coverage(start..=end), Some(extremum(max)));
+            assert_eq!(index.min_coverage(start..=end), Some(extremum(min)));
+
+            let mut histogram = BTreeMap::new();
+            for &value in window {
+                *histogram.entry(value).or_insert(0) += 1;
+            }
+            assert_eq!(index.histogram(start..=end), histogram);
+
+            let k = rng.range(0, 4) as i64;
+            let at_least = window.iter().filter(|&&value| value >= k).count();
+            assert_eq!(index.length_at_least(start..=end, k), at_least as u128);
+        }
+    }
+}
+
+#[test]
+fn test_analytics_on_huge_coordinates() {
+    let index = CoverageIndex::new(&[u64::MAX - 9..=u64::MAX, 1 << 62..=1 << 63, 0..=u64::MAX]);
+    assert_eq!(
+        index.max_coverage(0..=u64::MAX),
+        Some(Extremum {
+            value: 2,
+            first: 1 << 62,
+            last: u64::MAX,
+            count: 10 + (1 << 62) + 1,
+        })
+    );
+    assert_eq!(
+        index.min_coverage(u64::MAX - 20..=u64::MAX),
+        Some(Extremum {
+            value: 1,
+            first: u64::MAX - 20,
+            last: u64::MAX - 10,
+            count: 11,
+        })
+    );
+    assert_eq!(
+        index.histogram(0..=u64::MAX),
+        BTreeMap::from([(1, (1 << 64) - 10 - (1 << 62) - 1), (2, 10 + (1 << 62) + 1)])
+    );
+    assert_eq!(index.length_at_least(0..=u64::MAX, 1), 1 << 64);
+    assert_eq!(index.length_at_least(0..=u64::MAX, 3), 0);
+
+    let (start, end) = (5, 4);
+    assert_eq!(index.max_coverage(start..=end), None);
+    assert!(index.histogram(start..=end).is_empty());
+}
@@ -0,0 +1,120 @@
+mod common;
+
+use std::ops::Range;
+
+use handson2::fenwick::{FenwickTree, FenwickTree2D, RangeAddFenwick, RangeSumFenwick};
+
+use common::XorShift;
+
+#[test]
+fn test_point_update_prefix_sum_against_brute_force() {
+    for seed in 1..=30 {
+        let mut rng = XorShift(800 + seed);
+        let n = rng.range(0, 40) as usize;
+        let mut values: Vec<i64> = (0..n).map(|_| rng.range(-100, 100)).collect();
+        let mut tree: FenwickTree = values.iter().copied().collect();
+        assert_eq!(tree.len(), n);
+
+        for _ in 0..100 {
+            if n > 0 && rng.range(0, 1) == 0 {
+                let index = rng.range(0, n as u64 - 1) as usize;
+                let delta = rng.range(-100, 100);
+                values[index] += delta;
+                tree.add(index, delta);
+            }
+            let Range { start, end } = rng.subrange(n);
+            assert_eq!(tree.prefix_sum(end), values[..end].iter().sum::<i64>());
+            assert_eq!(tree.sum(start..end), values[start..end].iter().sum::<i64>());
+        }
+    }
+}
+
+#[test]
+fn test_lower_bound_against_brute_force() {
+    for seed in 1..=30 {
+        let mut rng = XorShift(830 + seed);
+        let n = rng.range(0, 40) as usize;
+        let values: Vec<i64> = (0..n).map(|_| rng.range(0, 5) as i64).collect();
+        let tree: FenwickTree = values.iter().copied().collect();
+        let total: i64 = values.iter().sum();
+
+        for target in -2..=total + 2 {
+            let expected = (0..=n).find(|&end| values[..end].iter().sum::<i64>() >= target);
+            assert_eq!(
+                tree.lower_bound(target),
+                expected,
+                "seed {seed}, target {target}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_range_add_variants_against_brute_force() {
+    for seed in 1..=30 {
+        let mut rng = XorShift(860 + seed);
+        let n = rng.range(0, 40) as usize;
+        let mut values: Vec<i64> = (0..n).map(|_| rng.range(-100, 100)).collect();
+        let mut point = RangeAddFenwick::new(n);
+        for (index, &value) in values.iter().enumerate() {
+            point.add(index..index + 1, value);
+        }
+        let mut ranged: RangeSumFenwick = values.iter().copied().collect();
+
+        for _ in 0..100 {
+            let Range { start, end } = rng.subrange(n);
+            if rng.range(0, 1) == 0 {
+                let delta = rng.range(-100, 100);
+                values[start..end]
+                    .iter_mut()
+                    .for_each(|value| *value += delta);
+                point.add(start..end, delta);
+                ranged.add(start..end, delta);
+            }
+            assert_eq!(
+                ranged.sum(start..end),
+                values[start..end].iter().sum::<i64>()
+            );
+            assert_eq!(ranged.prefix_sum(end), values[..end].iter().sum::<i64>());
+        }
+        for (index, &value) in values.iter().enumerate() {
+            assert_eq!(point.get(index), value);
+        }
+    }
+}
+
+#[test]
+fn test_2d_against_brute_force() {
+    for seed in 1..=20 {
+        let mut rng = XorShift(890 + seed);
+        let (rows, cols) = (rng.range(0, 12) as usize, rng.range(0, 12) as usize);
+        let mut grid = vec![vec![0_i64; cols]; rows];
+        let mut tree = FenwickTree2D::new(rows, cols);
+
+        for _ in 0..100 {
+            if rows > 0 && cols > 0 && rng.range(0, 1) == 0 {
+                let row = rng.range(0, rows as u64 - 1) as usize;
+                let col = rng.range(0, cols as u64 - 1) as usize;
+                let delta = rng.range(-100, 100);
+                grid[row][col] += delta;
+                tree.add(row, col, delta);
+            }
+            let r1 = rng.range(0, rows as u64) as usize;
+            let r2 = rng.range(r1 as u64, rows as u64) as usize;
+            let c1 = rng.range(0, cols as u64) as usize;
+            let c2 = rng.range(c1 as u64, cols as u64) as usize;
+            let expected: i64 = grid[r1..r2]
+                .iter()
+                .map(|row| row[c1..c2].iter().sum::<i64>())
+                .sum();
+            assert_eq!(tree.sum(r1..r2, c1..c2), expected);
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "Range 2..6 is out of bounds for length 5")]
+fn test_out_of_bounds_range_panics() {
+    let tree = FenwickTree::new(5);
+    tree.sum(2..6);
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use std::ops::Range;
+
+use handson2::historic_max::HistoricMaxTree;
+
+use common::XorShift;
+
+#[test]
+fn test_historic_max_against_brute_force() {
+    for seed in 1..=20 {
+        let mut rng = XorShift(340 + seed);
+        let n = rng.range(1, 40) as usize;
+        let mut values: Vec<i64> = (0..n).map(|_| rng.range(-50, 50)).collect();
+        let mut historic = values.clone();
+        let mut tree = HistoricMaxTree::new(&values);
+
+        for _ in 0..500 {
+            let Range { start, end } = rng.subrange(n);
+
+            if rng.range(0, 1) == 0 {
+                let x = rng.range(-60, 60);
+                tree.chmin(start..end, x);
+                values[start..end].iter_mut().for_each(|v| *v = (*v).min(x));
+            } else {
+                let add = rng.range(-20, 20);
+                tree.add(start..end, add);
+                values[start..end].iter_mut().for_each(|v| *v += add);
+            }
+            for (h, &v) in historic.iter_mut().zip(&values) {
+                *h = (*h).max(v);
+            }
+
+            let Range { start, end } = rng.subrange(n);
+            assert_eq!(
+                tree.historic_max(start..end),
+                historic[start..end].iter().max().copied(),
+                "historic max mismatch for seed {seed}"
+            );
+            assert_eq!(
+                tree.max(start..end),
+                values[start..end].iter().max().copied()
+            );
+            assert_eq!(tree.sum(start..end), values[start..end].iter().sum::<i64>());
+        }
+    }
+}
+
+#[test]
+fn test_historic_max_keeps_initial_values() {
+    let mut tree = HistoricMaxTree::new(&[7, 3, 9]);
+    tree.chmin(0..3, 1);
+    tree.add(0..3, -5);
+
+    assert_eq!(tree.max(0..3), Some(-4));
+    assert_eq!(tree.historic_max(0..1), Some(7));
+    assert_eq!(tree.historic_max(1..3), Some(9));
+    assert_eq!(tree.historic_max(2..2), None);
+}
+
+#[test]
+fn test_extreme_values() {
+    // i64::MIN is a real second maximum and must shift with the add
+    let mut tree = HistoricMaxTree::new(&[i64::MIN, 5, i64::MIN, 5]);
+    tree.add(0..4, 3);
+    tree.chmin(0..4, i64::MIN + 2);
+    assert_eq!(tree.max(0..4), Some(i64::MIN + 2));
+    assert_eq!(tree.max(0..1), Some(i64::MIN + 2));
+    assert_eq!(tree.historic_max(0..1), Some(i64::MIN + 3));
+    assert_eq!(tree.historic_max(0..4), Some(8));
+
+    // Partial sums overflow, the totals fit
+    let mut tree = HistoricMaxTree::new(&[i64::MIN, -1, 1, i64::MAX]);
+    assert_eq!(tree.sum(0..3), i64::MIN);
+    assert_eq!(tree.sum(0..4), -1);
+    tree.add(1..2, 1);
+    assert_eq!(tree.sum(0..2), i64::MIN);
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use handson2::interval_tree::IntervalTree;
+
+use common::XorShift;
+
+fn sorted(mut ids: Vec<usize>) -> Vec<usize> {
+    ids.sort_unstable();
+    ids
+}
+
+#[test]
+fn test_stab_and_overlapping_against_brute_force() {
+    for seed in 1..=30 {
+        let mut rng = XorShift(420 + seed);
+        let segments: Vec<_> = (0..rng.range(0, 40))
+            .map(|_| {
+                let left = rng.range(0, 60);
+                left..=rng.range(left, 60)
+            })
+            .collect();
+        let tree = IntervalTree::new(&segments);
+        assert_eq!(tree.len(), segments.len());
+
+        for x in 0..=62 {
+            let expected: Vec<usize> = (0..segments.len())
+                .filter(|&id| segments[id].contains(&x))
+                .collect();
+            assert_eq!(sorted(tree.stab(x)), expected, "stab({x}), seed {seed}");
+        }
+
+        for _ in 0..100 {
+            let start = rng.range(0, 62);
+            let end = rng.range(start, 62);
+            let expected: Vec<usize> = (0..segments.len())
+                .filter(|&id| *segments[id].start() <= end && start <= *segments[id].end())
+                .collect();
+            assert_eq!(
+                sorted(tree.overlapping(start..=end)),
+                expected,
+                "overlapping({start}..={end}), seed {seed}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_duplicates_and_extreme_coordinates() {
+    let segments = [0..=u64::MAX, 5..=5, 5..=5, u64::MAX..=u64::MAX];
+    let tree: IntervalTree = segments.iter().cloned().collect();
+
+    assert_eq!(sorted(tree.stab(5)), vec![0, 1, 2]);
+    assert_eq!(sorted(tree.stab(u64::MAX)), vec![0, 3]);
+    assert_eq!(sorted(tree.overlapping(6..=u64::MAX)), vec![0, 3]);
+
+    let (start, end) = (9, 8);
+    assert!(tree.overlapping(start..=end).is_empty());
+    assert!(IntervalTree::new(&[]).stab(0).is_empty());
+}
+
+#[test]
+fn test_built_from_problem2_input() {
+    let input = std::fs::read_to_string("tests/data/problem2/input0.txt").unwrap();
+    let segments = handson2::problem2::parse_segments(&input);
+    let tree = IntervalTree::new(&segments);
+    let index = handson2::coverage_index::CoverageIndex::new(&segments);
+
+    let max_coord = segments.iter().map(|seg| *seg.end()).max().unwrap();
+    for x in 0..=max_coord + 1 {
+        assert_eq!(tree.stab(x).len() as i64, index.coverage_at(x), "stab({x})");
+    }
+}
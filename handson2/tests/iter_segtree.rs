@@ -0,0 +1,169 @@
+mod common;
+
+use handson2::iter_segtree::IterLazySegTree;
+use handson2::lazy_segtree::{Action, Add, LazySegTree, MinMax, MinMaxValue, Monoid, Sum, SumLen};
+
+use common::XorShift;
+
+/// Max with strict second max: a minimal beats monoid whose chmin action
+/// fails on nodes where `x <= second`.
+struct MaxSecond;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct MaxSecondValue {
+    max: i64,
+    second: i64,
+}
+
+impl Monoid for MaxSecond {
+    type Value = MaxSecondValue;
+
+    fn identity() -> MaxSecondValue {
+        MaxSecondValue {
+            max: i64::MIN,
+            second: i64::MIN,
+        }
+    }
+
+    fn combine(left: &MaxSecondValue, right: &MaxSecondValue) -> MaxSecondValue {
+        let max = left.max.max(right.max);
+        let second = [left.max, left.second, right.max, right.second]
+            .into_iter()
+            .filter(|&v| v < max)
+            .max()
+            .unwrap_or(i64::MIN);
+        MaxSecondValue { max, second }
+    }
+}
+
+struct Chmin;
+
+impl Action<MaxSecond> for Chmin {
+    type Tag = i64;
+
+    fn identity() -> i64 {
+        i64::MAX
+    }
+
+    fn compose(outer: &i64, inner: &i64) -> i64 {
+        *outer.min(inner)
+    }
+
+    fn apply(x: &i64, value: &MaxSecondValue) -> Option<MaxSecondValue> {
+        if value.max <= *x {
+            Some(*value)
+        } else if value.second < *x {
+            Some(MaxSecondValue {
+                max: *x,
+                second: value.second,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+#[test]
+fn test_sum_add_matches_recursive_backend() {
+    for n in [0, 1, 2, 3, 7, 8, 9, 100] {
+        let mut rng = XorShift(31 + n as u64);
+        let values: Vec<SumLen> = (0..n).map(|_| SumLen::from(rng.range(-50, 50))).collect();
+        let mut recursive: LazySegTree<Sum, Add> = LazySegTree::new(&values);
+        let mut iterative: IterLazySegTree<Sum, Add> = IterLazySegTree::new(&values);
+
+        for _ in 0..500 {
+            let range = rng.subrange(n);
+            let add = rng.range(-10, 10);
+            recursive.apply(range.clone(), &add);
+            iterative.apply(range, &add);
+
+            let range = rng.subrange(n);
+            assert_eq!(iterative.query(range.clone()), recursive.query(range));
+        }
+        assert_eq!(iterative.to_vec(), recursive.to_vec());
+    }
+}
+
+#[test]
+fn test_find_first_matches_recursive_backend() {
+    let mut rng = XorShift(310);
+    let n = 45;
+    let values: Vec<MinMaxValue> = (0..n).map(|_| MinMaxValue::from(rng.range(0, 6))).collect();
+    let mut recursive: LazySegTree<MinMax, Add> = LazySegTree::new(&values);
+    let mut iterative: IterLazySegTree<MinMax, Add> = IterLazySegTree::new(&values);
+
+    for _ in 0..2000 {
+        let range = rng.subrange(n);
+        let add = rng.range(-2, 2);
+        recursive.apply(range.clone(), &add);
+        iterative.apply(range, &add);
+
+        let range = rng.subrange(n);
+        let k = rng.range(-5, 10);
+        let pred = |node: &MinMaxValue| node.min <= k && k <= node.max;
+        assert_eq!(
+            iterative.find_first(range.clone(), pred),
+            recursive.find_first(range, pred)
+        );
+    }
+}
+
+#[test]
+fn test_failing_action_matches_brute_force() {
+    for n in [1, 5, 16, 33] {
+        let mut rng = XorShift(3100 + n as u64);
+        let mut values: Vec<i64> = (0..n).map(|_| rng.range(0, 1000)).collect();
+        let leaves: Vec<MaxSecondValue> = values
+            .iter()
+            .map(|&v| MaxSecondValue {
+                max: v,
+                second: i64::MIN,
+            })
+            .collect();
+        let mut recursive: LazySegTree<MaxSecond, Chmin> = LazySegTree::new(&leaves);
+        let mut iterative: IterLazySegTree<MaxSecond, Chmin> = IterLazySegTree::new(&leaves);
+
+        for _ in 0..500 {
+            let range = rng.subrange(n);
+            let x = rng.range(0, 1000);
+            recursive.apply(range.clone(), &x);
+            iterative.apply(range.clone(), &x);
+            values[range].iter_mut().for_each(|v| *v = (*v).min(x));
+
+            let range = rng.subrange(n);
+            let expected = values[range.clone()]
+                .iter()
+                .max()
+                .copied()
+                .unwrap_or(i64::MIN);
+            assert_eq!(iterative.query(range.clone()).max, expected);
+            assert_eq!(recursive.query(range).max, expected);
+        }
+
+        let maxima: Vec<i64> = iterative.to_vec().iter().map(|v| v.max).collect();
+        assert_eq!(maxima, values);
+    }
+}
+
+#[test]
+fn test_max_right_and_min_left_match_recursive_backend() {
+    for n in [1, 2, 5, 8, 13, 64] {
+        let mut rng = XorShift(3200 + n as u64);
+        let values: Vec<SumLen> = (0..n).map(|_| SumLen::from(rng.range(0, 10))).collect();
+        let mut recursive: LazySegTree<Sum, Add> = LazySegTree::new(&values);
+        let mut iterative: IterLazySegTree<Sum, Add> = IterLazySegTree::new(&values);
+
+        for _ in 0..500 {
+            let range = rng.subrange(n);
+            let add = rng.range(0, 3);
+            recursive.apply(range.clone(), &add);
+            iterative.apply(range, &add);
+
+            let limit = rng.range(0, 60);
+            let i = rng.range(0, n as i64) as usize;
+            let pred = |v: &SumLen| v.sum <= limit;
+            assert_eq!(iterative.max_right(i, pred), recursive.max_right(i, pred));
+            assert_eq!(iterative.min_left(i, pred), recursive.min_left(i, pred));
+        }
+    }
+}
@@ -0,0 +1,218 @@
+mod common;
+
+use handson2::historic_max::HistoricMaxTree;
+use handson2::iter_segtree::IterLazySegTree;
+use handson2::journal::Checkpoint;
+use handson2::lazy_segtree::{Add, LazySegTree, Sum, SumLen};
+use handson2::problem1::SegmentTree;
+
+use common::XorShift;
+
+/// The operations shared by every tree with a journal.
+trait Undoable {
+    fn random_update(&mut self, rng: &mut XorShift);
+    /// Full observable state; may push tags down (which is journaled too).
+    fn snapshot(&mut self) -> Vec<i64>;
+    fn checkpoint(&mut self) -> Checkpoint;
+    fn rollback(&mut self, checkpoint: Checkpoint);
+    fn commit(&mut self, checkpoint: Checkpoint);
+}
+
+impl Undoable for LazySegTree<Sum, Add> {
+    fn random_update(&mut self, rng: &mut XorShift) {
+        let range = rng.subrange(self.len());
+        self.apply(range, &rng.range(-20, 20));
+    }
+
+    fn snapshot(&mut self) -> Vec<i64> {
+        self.to_vec().iter().map(|value| value.sum).collect()
+    }
+
+    fn checkpoint(&mut self) -> Checkpoint {
+        LazySegTree::checkpoint(self)
+    }
+
+    fn rollback(&mut self, checkpoint: Checkpoint) {
+        LazySegTree::rollback(self, checkpoint);
+    }
+
+    fn commit(&mut self, checkpoint: Checkpoint) {
+        LazySegTree::commit(self, checkpoint);
+    }
+}
+
+impl Undoable for IterLazySegTree<Sum, Add> {
+    fn random_update(&mut self, rng: &mut XorShift) {
+        let range = rng.subrange(self.len());
+        self.apply(range, &rng.range(-20, 20));
+    }
+
+    fn snapshot(&mut self) -> Vec<i64> {
+        self.to_vec().iter().map(|value| value.sum).collect()
+    }
+
+    fn checkpoint(&mut self) -> Checkpoint {
+        IterLazySegTree::checkpoint(self)
+    }
+
+    fn rollback(&mut self, checkpoint: Checkpoint) {
+        IterLazySegTree::rollback(self, checkpoint);
+    }
+
+    fn commit(&mut self, checkpoint: Checkpoint) {
+        IterLazySegTree::commit(self, checkpoint);
+    }
+}
+
+impl Undoable for SegmentTree {
+    fn random_update(&mut self, rng: &mut XorShift) {
+        let range = rng.subrange(self.len());
+        let x = rng.range(-30, 30);
+        match rng.range(0, 3) {
+            0 => self.chmin(range, x),
+            1 => self.chmax(range, x),
+            2 => self.add(range, x),
+            _ => self.assign(range, x),
+        }
+    }
+
+    fn snapshot(&mut self) -> Vec<i64> {
+        self.to_vec()
+    }
+
+    fn checkpoint(&mut self) -> Checkpoint {
+        SegmentTree::checkpoint(self)
+    }
+
+    fn rollback(&mut self, checkpoint: Checkpoint) {
+        SegmentTree::rollback(self, checkpoint);
+    }
+
+    fn commit(&mut self, checkpoint: Checkpoint) {
+        SegmentTree::commit(self, checkpoint);
+    }
+}
+
+impl Undoable for HistoricMaxTree {
+    fn random_update(&mut self, rng: &mut XorShift) {
+        let range = rng.subrange(self.len());
+        if rng.range(0, 1) == 0 {
+            self.chmin(range, rng.range(-30, 30));
+        } else {
+            self.add(range, rng.range(-20, 20));
+        }
+    }
+
+    fn snapshot(&mut self) -> Vec<i64> {
+        (0..self.len())
+            .flat_map(|k| [self.max(k..k + 1), self.historic_max(k..k + 1)])
+            .map(Option::unwrap)
+            .collect()
+    }
+
+    fn checkpoint(&mut self) -> Checkpoint {
+        HistoricMaxTree::checkpoint(self)
+    }
+
+    fn rollback(&mut self, checkpoint: Checkpoint) {
+        HistoricMaxTree::rollback(self, checkpoint);
+    }
+
+    fn commit(&mut self, checkpoint: Checkpoint) {
+        HistoricMaxTree::commit(self, checkpoint);
+    }
+}
+
+/// Interleaves updates with nested checkpoints, rollbacks and commits, and
+/// checks that every rollback restores the snapshot taken at its checkpoint.
+fn check_nested_rollbacks<T: Undoable>(make: impl Fn(&[i64]) -> T) {
+    for seed in 1..=10 {
+        let mut rng = XorShift(360 + seed);
+        let n = rng.range(1, 30) as usize;
+        let values: Vec<i64> = (0..n).map(|_| rng.range(-50, 50)).collect();
+        let mut tree = make(&values);
+        let mut open: Vec<(Checkpoint, Vec<i64>)> = Vec::new();
+
+        for _ in 0..400 {
+            match rng.range(0, 9) {
+                0..=5 => tree.random_update(&mut rng),
+                6 => {
+                    let snapshot = tree.snapshot();
+                    open.push((tree.checkpoint(), snapshot));
+                }
+                7 | 8 if !open.is_empty() => {
+                    let depth = rng.range(0, open.len() as i64 - 1) as usize;
+                    let (checkpoint, snapshot) = open.drain(depth..).next().unwrap();
+                    tree.rollback(checkpoint);
+                    assert_eq!(tree.snapshot(), snapshot, "seed {seed}");
+                }
+                9 if !open.is_empty() => {
+                    let depth = rng.range(0, open.len() as i64 - 1) as usize;
+                    let (checkpoint, _) = open.drain(depth..).next().unwrap();
+                    tree.commit(checkpoint);
+                }
+                _ => {}
+            }
+        }
+
+        while let Some((checkpoint, snapshot)) = open.pop() {
+            tree.rollback(checkpoint);
+            assert_eq!(tree.snapshot(), snapshot, "seed {seed}");
+        }
+    }
+}
+
+#[test]
+fn test_lazy_segtree_rollback() {
+    check_nested_rollbacks(|values| {
+        values
+            .iter()
+            .map(|&v| SumLen::from(v))
+            .collect::<LazySegTree<Sum, Add>>()
+    });
+}
+
+#[test]
+fn test_iter_segtree_rollback() {
+    check_nested_rollbacks(|values| {
+        values
+            .iter()
+            .map(|&v| SumLen::from(v))
+            .collect::<IterLazySegTree<Sum, Add>>()
+    });
+}
+
+#[test]
+fn test_beats_segment_tree_rollback() {
+    check_nested_rollbacks(SegmentTree::new);
+}
+
+#[test]
+fn test_historic_max_rollback() {
+    check_nested_rollbacks(HistoricMaxTree::new);
+}
+
+#[test]
+fn test_commit_keeps_changes() {
+    let mut tree = SegmentTree::new(&[4, 8, 1]);
+    let outer = tree.checkpoint();
+    tree.chmin(0..3, 5);
+    let inner = tree.checkpoint();
+    tree.add(0..2, 10);
+    tree.commit(inner);
+    assert_eq!(tree.to_vec(), vec![14, 15, 1]);
+
+    tree.rollback(outer);
+    assert_eq!(tree.to_vec(), vec![4, 8, 1]);
+}
+
+#[test]
+#[should_panic(expected = "already released")]
+fn test_released_checkpoint_panics() {
+    let mut tree = SegmentTree::new(&[4, 8, 1]);
+    let outer = tree.checkpoint();
+    let inner = tree.checkpoint();
+    tree.rollback(outer);
+    let _again = tree.checkpoint();
+    tree.rollback(inner);
+}
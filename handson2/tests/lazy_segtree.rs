@@ -0,0 +1,188 @@
+mod common;
+
+use std::ops::Range;
+
+use handson2::lazy_segtree::{Add, LazySegTree, Max, Min, MinMax, MinMaxValue, Sum, SumLen};
+
+use common::XorShift;
+
+#[test]
+fn test_add_on_every_monoid_against_brute_force() {
+    let mut rng = XorShift(30);
+    let n = 37;
+    let mut values: Vec<i64> = (0..n).map(|_| rng.range(-50, 50)).collect();
+
+    let mut sums: LazySegTree<Sum, Add> = values.iter().copied().map(SumLen::from).collect();
+    let mut mins: LazySegTree<Min, Add> = LazySegTree::new(&values);
+    let mut maxs: LazySegTree<Max, Add> = LazySegTree::new(&values);
+    let mut min_maxs: LazySegTree<MinMax, Add> =
+        values.iter().copied().map(MinMaxValue::from).collect();
+
+    for _ in 0..1000 {
+        let Range { start, end } = rng.subrange(n);
+        let add = rng.range(-10, 10);
+
+        sums.apply(start..end, &add);
+        mins.apply(start..end, &add);
+        maxs.apply(start..end, &add);
+        min_maxs.apply(start..end, &add);
+        values[start..end].iter_mut().for_each(|v| *v += add);
+
+        let Range { start, end } = rng.subrange(n);
+        let slice = &values[start..end];
+
+        assert_eq!(sums.query(start..end).sum, slice.iter().sum::<i64>());
+        assert_eq!(sums.query(start..end).len, slice.len());
+        assert_eq!(
+            mins.query(start..end),
+            slice.iter().min().copied().unwrap_or(i64::MAX)
+        );
+        assert_eq!(
+            maxs.query(start..end),
+            slice.iter().max().copied().unwrap_or(i64::MIN)
+        );
+        if !slice.is_empty() {
+            assert_eq!(
+                min_maxs.query(start..end),
+                MinMaxValue {
+                    min: *slice.iter().min().unwrap(),
+                    max: *slice.iter().max().unwrap(),
+                }
+            );
+        }
+    }
+
+    assert_eq!(mins.to_vec(), values);
+}
+
+#[test]
+fn test_find_first_with_min_max_pruning() {
+    let values = [3, 1, 4, 1, 5, 9, 2, 6, 5, 3];
+    let mut tree: LazySegTree<MinMax, Add> =
+        values.iter().copied().map(MinMaxValue::from).collect();
+
+    for k in 0..=10 {
+        for start in 0..values.len() {
+            for end in start..=values.len() {
+                let expected = (start..end).find(|&i| values[i] == k);
+                let got = tree.find_first(start..end, |node| node.min <= k && k <= node.max);
+                assert_eq!(got, expected, "k = {k}, range {start}..{end}");
+            }
+        }
+    }
+
+    // Pending additions must be pushed down before descending.
+    tree.apply(2..6, &10);
+    assert_eq!(
+        tree.find_first(0..10, |node| node.min <= 11 && 11 <= node.max),
+        Some(3)
+    );
+}
+
+#[test]
+fn test_empty_tree_and_ranges() {
+    let mut tree: LazySegTree<Sum, Add> = LazySegTree::new(&[]);
+    assert!(tree.is_empty());
+    assert_eq!(tree.query(0..0), SumLen { sum: 0, len: 0 });
+    tree.apply(0..0, &5);
+    assert!(tree.to_vec().is_empty());
+}
+
+#[test]
+#[should_panic(expected = "out of bounds")]
+fn test_out_of_bounds_range() {
+    let mut tree: LazySegTree<Max, Add> = LazySegTree::new(&[1, 2, 3]);
+    tree.query(2..4);
+}
+
+#[test]
+fn test_max_right_and_min_left_on_prefix_sums() {
+    let mut rng = XorShift(320);
+    let n = 50;
+    let mut values: Vec<i64> = (0..n).map(|_| rng.range(0, 10)).collect();
+    let mut tree: LazySegTree<Sum, Add> = values.iter().copied().map(SumLen::from).collect();
+
+    for _ in 0..1000 {
+        let Range { start, end } = rng.subrange(n);
+        let add = rng.range(0, 3);
+        tree.apply(start..end, &add);
+        values[start..end].iter_mut().for_each(|v| *v += add);
+
+        let limit = rng.range(0, 100);
+        let i = rng.range(0, n as i64) as usize;
+
+        // Largest end with sum(i..end) <= limit (values are non-negative).
+        let expected = (i..=n)
+            .take_while(|&e| values[i..e].iter().sum::<i64>() <= limit)
+            .last()
+            .unwrap();
+        assert_eq!(tree.max_right(i, |v| v.sum <= limit), expected);
+
+        // Smallest start with sum(start..i) <= limit.
+        let expected = (0..=i)
+            .rev()
+            .take_while(|&s| values[s..i].iter().sum::<i64>() <= limit)
+            .last()
+            .unwrap();
+        assert_eq!(tree.min_left(i, |v| v.sum <= limit), expected);
+    }
+}
+
+#[test]
+fn test_monoids_on_extreme_values() {
+    // Extremes are ordinary data: the identities are never read as "missing"
+    let values = [i32::MAX, i32::MIN, i32::MAX, 0];
+    let mut mins: LazySegTree<Min<i32>, Add<i32>> = LazySegTree::new(&values);
+    assert_eq!(mins.query(0..1), i32::MAX);
+    assert_eq!(mins.query(0..4), i32::MIN);
+    assert_eq!(mins.find_first(0..4, |&min| min < i32::MAX), Some(1));
+
+    let values = [u64::MAX, 0, u64::MAX, 7];
+    let mut maxs: LazySegTree<Max<u64>, Add<u64>> = LazySegTree::new(&values);
+    assert_eq!(maxs.query(1..2), 0);
+    assert_eq!(maxs.query(1..4), u64::MAX);
+    maxs.apply(1..2, &u64::MAX);
+    assert_eq!(maxs.to_vec(), vec![u64::MAX, u64::MAX, u64::MAX, 7]);
+
+    let values = [i128::MIN, i128::MAX];
+    let mut min_maxs: LazySegTree<MinMax<i128>, Add<i128>> =
+        values.iter().copied().map(MinMaxValue::from).collect();
+    assert_eq!(
+        min_maxs.query(0..2),
+        MinMaxValue {
+            min: i128::MIN,
+            max: i128::MAX
+        }
+    );
+    // Only the empty range has min > max
+    let empty = min_maxs.query(1..1);
+    assert!(empty.min > empty.max);
+
+    // Partial sums overflow, the total does not
+    let values = [i64::MAX, i64::MAX, i64::MIN, i64::MIN];
+    let mut sums: LazySegTree<Sum<i64>, Add<i64>> =
+        values.iter().copied().map(SumLen::from).collect();
+    assert_eq!(sums.query(0..4).sum, -2);
+    sums.apply(2..4, &1);
+    assert_eq!(sums.query(0..4).sum, 0);
+}
+
+#[test]
+fn test_pending_additions_may_cancel_out() {
+    // The tags `i64::MAX` and `1` compose to 2^63, every value stays in range
+    let mut mins: LazySegTree<Min, Add> = LazySegTree::new(&[i64::MIN, i64::MIN]);
+    mins.apply(0..2, &i64::MAX);
+    mins.apply(0..2, &1);
+    assert_eq!(mins.query(0..1), 0);
+    assert_eq!(mins.to_vec(), vec![0, 0]);
+    mins.apply(0..2, &i64::MIN);
+    assert_eq!(mins.query(0..2), i64::MIN);
+
+    let mut min_maxs: LazySegTree<MinMax, Add> = [i64::MIN, i64::MIN + 1]
+        .into_iter()
+        .map(MinMaxValue::from)
+        .collect();
+    min_maxs.apply(0..2, &i64::MAX);
+    min_maxs.apply(0..2, &1);
+    assert_eq!(min_maxs.query(0..2), MinMaxValue { min: 0, max: 1 });
+}
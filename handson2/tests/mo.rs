@@ -0,0 +1,124 @@
+mod common;
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use handson2::mo::{MoState, Order, process};
+
+use common::XorShift;
+
+/// Number of distinct values and frequency of the mode of the window.
+struct Frequencies<'a> {
+    values: &'a [u64],
+    counts: HashMap<u64, usize>,
+    /// `with_count[c]` = number of values occurring exactly `c` times.
+    with_count: Vec<usize>,
+    mode: usize,
+    /// Add / remove calls made so far.
+    moves: usize,
+}
+
+impl<'a> Frequencies<'a> {
+    fn new(values: &'a [u64]) -> Self {
+        Self {
+            values,
+            counts: HashMap::new(),
+            with_count: vec![0; values.len() + 1],
+            mode: 0,
+            moves: 0,
+        }
+    }
+}
+
+impl MoState for Frequencies<'_> {
+    type Answer = (usize, usize);
+
+    fn add(&mut self, index: usize) {
+        self.moves += 1;
+        let count = self.counts.entry(self.values[index]).or_default();
+        self.with_count[*count] -= usize::from(*count > 0);
+        *count += 1;
+        self.with_count[*count] += 1;
+        self.mode = self.mode.max(*count);
+    }
+
+    fn remove(&mut self, index: usize) {
+        self.moves += 1;
+        let count = self
+            .counts
+            .get_mut(&self.values[index])
+            .expect("Value is in the window");
+        self.with_count[*count] -= 1;
+        if self.mode == *count && self.with_count[*count] == 0 {
+            self.mode -= 1;
+        }
+        *count -= 1;
+        if *count == 0 {
+            self.counts.remove(&self.values[index]);
+        } else {
+            self.with_count[*count] += 1;
+        }
+    }
+
+    fn answer(&self, _query: usize) -> (usize, usize) {
+        (self.counts.len(), self.mode)
+    }
+}
+
+fn brute_force(values: &[u64], range: Range<usize>) -> (usize, usize) {
+    let mut counts: HashMap<u64, usize> = HashMap::new();
+    for &value in &values[range] {
+        *counts.entry(value).or_default() += 1;
+    }
+    (counts.len(), counts.values().copied().max().unwrap_or(0))
+}
+
+#[test]
+fn test_distinct_and_mode_against_brute_force() {
+    for seed in 1..=40 {
+        let mut rng = XorShift(700 + seed);
+        let n = rng.range(0, 60) as usize;
+        let values: Vec<u64> = (0..n).map(|_| rng.range(0, 8)).collect();
+        let queries: Vec<Range<usize>> = (0..rng.range(0, 50)).map(|_| rng.subrange(n)).collect();
+        let expected: Vec<_> = queries
+            .iter()
+            .map(|range| brute_force(&values, range.clone()))
+            .collect();
+
+        for order in [Order::Blocks, Order::Hilbert] {
+            let mut state = Frequencies::new(&values);
+            assert_eq!(
+                process(&mut state, n, &queries, order),
+                expected,
+                "seed {seed} with {order:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_window_moves_stay_subquadratic() {
+    let n = 4_000;
+    let mut rng = XorShift(750);
+    let values: Vec<u64> = (0..n).map(|_| rng.range(0, 100)).collect();
+    let queries: Vec<Range<usize>> = (0..n).map(|_| rng.subrange(n)).collect();
+
+    // Visiting the queries in input order moves the window ~n²/3 steps
+    for order in [Order::Blocks, Order::Hilbert] {
+        let mut state = Frequencies::new(&values);
+        process(&mut state, n, &queries, order);
+        assert!(
+            state.moves < 4 * n * n.isqrt(),
+            "{order:?} made {} moves",
+            state.moves
+        );
+    }
+}
+
+#[test]
+#[should_panic(expected = "Range 3..7 is out of bounds for length 5")]
+fn test_out_of_bounds_query_panics() {
+    let values = [0; 5];
+    let mut state = Frequencies::new(&values);
+    process(&mut state, values.len(), &[0..2, 3..7], Order::default());
+}
@@ -0,0 +1,119 @@
+mod common;
+
+use std::ops::Range;
+
+use handson2::persistent_segtree::PersistentSegTree;
+
+use common::XorShift;
+
+#[test]
+fn test_every_version_against_brute_force() {
+    for seed in 1..=20 {
+        let mut rng = XorShift(350 + seed);
+        let n = rng.range(1, 40) as usize;
+        let initial: Vec<i64> = (0..n).map(|_| rng.range(-50, 50)).collect();
+        let mut tree = PersistentSegTree::new(&initial);
+        let mut history = vec![initial];
+
+        for step in 1..=300 {
+            let mut values = history.last().unwrap().clone();
+            let version = if rng.range(0, 2) == 0 {
+                let index = rng.range(0, n as i64 - 1) as usize;
+                let value = rng.range(-60, 60);
+                values[index] = value;
+                tree.set(index, value)
+            } else {
+                let Range { start, end } = rng.subrange(n);
+                let x = rng.range(-60, 60);
+                values[start..end].iter_mut().for_each(|v| *v = (*v).min(x));
+                tree.chmin(start..end, x)
+            };
+            assert_eq!(version, step);
+            history.push(values);
+
+            // Query a random past version.
+            let version = rng.range(0, step as i64) as usize;
+            let Range { start, end } = rng.subrange(n);
+            assert_eq!(
+                tree.max(version, start..end),
+                history[version][start..end].iter().max().copied(),
+                "max mismatch for seed {seed}, version {version}"
+            );
+        }
+
+        for (version, values) in history.iter().enumerate() {
+            assert_eq!(&tree.to_vec(version), values, "seed {seed}");
+        }
+    }
+}
+
+#[test]
+fn test_replaying_problem1_streams_preserves_answers() {
+    for i in 0..=10 {
+        let input = std::fs::read_to_string(format!("tests/data/problem1/input{i}.txt")).unwrap();
+        let expected =
+            std::fs::read_to_string(format!("tests/data/problem1/output{i}.txt")).unwrap();
+        let mut iterator = input
+            .split_ascii_whitespace()
+            .map(|s| s.parse::<i64>().unwrap());
+
+        let n = iterator.next().unwrap() as usize;
+        let q = iterator.next().unwrap() as usize;
+        let values: Vec<i64> = iterator.by_ref().take(n).collect();
+        let mut tree = PersistentSegTree::new(&values);
+
+        // (version, range) of every Max query, answered after all updates.
+        let mut queries = Vec::new();
+        for _ in 0..q {
+            let op = iterator.next().unwrap();
+            let start = iterator.next().unwrap() as usize - 1;
+            let end = iterator.next().unwrap() as usize;
+            if op == 0 {
+                tree.chmin(start..end, iterator.next().unwrap());
+            } else {
+                queries.push((tree.latest(), start..end));
+            }
+        }
+
+        let got: Vec<String> = queries
+            .into_iter()
+            .map(|(version, range)| tree.max(version, range).unwrap().to_string())
+            .collect();
+        let expected: Vec<&str> = expected.split_whitespace().collect();
+        assert_eq!(got, expected, "Mismatch in case {i}");
+    }
+}
+
+#[test]
+fn test_empty_tree_and_ranges() {
+    let mut tree = PersistentSegTree::new(&[]);
+    assert!(tree.is_empty());
+    assert_eq!(tree.chmin(0..0, 3), 1);
+    assert_eq!(tree.max(1, 0..0), None);
+    assert_eq!(tree.to_vec(0), Vec::<i64>::new());
+}
+
+#[test]
+#[should_panic(expected = "does not exist")]
+fn test_unknown_version_panics() {
+    let tree: PersistentSegTree = [1, 2, 3].into_iter().collect();
+    tree.max(1, 0..3);
+}
+
+#[test]
+fn test_extreme_values() {
+    let mut tree = PersistentSegTree::new(&[i64::MIN, 5, i64::MIN, i64::MAX]);
+    let v1 = tree.chmin(0..4, i64::MIN + 1);
+    let v2 = tree.chmin(0..4, i64::MIN);
+    let v3 = tree.set(2, i64::MAX);
+    assert_eq!(tree.max(0, 0..3), Some(5));
+    assert_eq!(tree.max(0, 2..3), Some(i64::MIN));
+    assert_eq!(
+        tree.to_vec(v1),
+        [i64::MIN, i64::MIN + 1, i64::MIN, i64::MIN + 1]
+    );
+    assert_eq!(tree.to_vec(v2), [i64::MIN; 4]);
+    assert_eq!(tree.max(v2, 0..4), Some(i64::MIN));
+    assert_eq!(tree.max(v3, 0..4), Some(i64::MAX));
+    assert_eq!(tree.max(v3, 0..2), Some(i64::MIN));
+}
@@ -1,25 +1,13 @@
-/// Normalize txt test file line endings and trailing spaces
-fn normalize(s: &str) -> String {
-    s.replace("\r\n", "\n").trim_end().to_string()
-}
-
-fn load_case(i: usize) -> (String, String) {
-    let input_path = format!("data/problem1/input{}.txt", i);
-    let output_path = format!("data/problem1/output{}.txt", i);
-
-    let input = std::fs::read_to_string(std::path::Path::new("tests").join(&input_path))
-        .unwrap_or_else(|_| panic!("Cannot read {}", input_path));
+mod common;
 
-    let expected = std::fs::read_to_string(std::path::Path::new("tests").join(&output_path))
-        .unwrap_or_else(|_| panic!("Cannot read {}", output_path));
+use std::ops::Range;
 
-    (input, expected)
-}
+use common::{XorShift, load_case, normalize};
 
 #[test]
 fn test_all_problem1_cases() {
-    for i in 0..=10 {
-        let (input, expected) = load_case(i);
+    for i in 0..=11 {
+        let (input, expected) = load_case("problem1", i);
         let got = handson2::problem1::solve(&input);
 
         assert_eq!(
@@ -30,3 +18,383 @@ fn test_all_problem1_cases() {
         );
     }
 }
+
+/// Generates a random stream mixing every operation type and the expected
+/// output computed by brute force on a plain vector.
+fn random_case(seed: u64, n: usize, q: usize) -> (String, String) {
+    let mut rng = XorShift(seed);
+    let mut values: Vec<i64> = (0..n).map(|_| rng.range(-100, 100)).collect();
+
+    let mut input = format!("{n} {q}\n");
+    input += &values
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(" ");
+    input.push('\n');
+
+    let mut expected = Vec::new();
+    for _ in 0..q {
+        let op = rng.range(0, 6);
+        let i = rng.range(1, n as i64) as usize;
+        let j = rng.range(i as i64, n as i64) as usize;
+        let range = &mut values[i - 1..j];
+
+        match op {
+            0 | 2 | 3 | 4 => {
+                let v = if op == 3 {
+                    rng.range(-30, 30)
+                } else {
+                    rng.range(-120, 120)
+                };
+                input += &format!("{op} {i} {j} {v}\n");
+                for a in range.iter_mut() {
+                    *a = match op {
+                        0 => (*a).min(v),
+                        2 => (*a).max(v),
+                        3 => *a + v,
+                        _ => v,
+                    };
+                }
+            }
+            _ => {
+                input += &format!("{op} {i} {j}\n");
+                let ans = match op {
+                    1 => *range.iter().max().unwrap(),
+                    5 => range.iter().sum(),
+                    _ => *range.iter().min().unwrap(),
+                };
+                expected.push(ans.to_string());
+            }
+        }
+    }
+
+    (input, expected.join("\n"))
+}
+
+#[test]
+fn test_random_mixed_operations_against_brute_force() {
+    for seed in 1..=30 {
+        let n = 1 + (seed as usize * 7) % 60;
+        let (input, expected) = random_case(seed, n, 400);
+        let got = handson2::problem1::solve(&input);
+
+        assert_eq!(got, expected, "Mismatch for random seed {}", seed);
+    }
+}
+
+#[test]
+fn test_public_api_against_brute_force() {
+    use handson2::problem1::SegmentTree;
+
+    let mut rng = XorShift(2024);
+    let mut values: Vec<i64> = (0..50).map(|_| rng.range(-1000, 1000)).collect();
+    let mut tree: SegmentTree = values.iter().copied().collect();
+    assert_eq!(tree.len(), values.len());
+
+    for _ in 0..2000 {
+        let Range { start, end } = rng.subrange(50);
+        let x = rng.range(-1000, 1000);
+        let range = &mut values[start..end];
+
+        match rng.range(0, 3) {
+            0 => {
+                tree.chmin(start..end, x);
+                range.iter_mut().for_each(|a| *a = (*a).min(x));
+            }
+            1 => {
+                tree.chmax(start..end, x);
+                range.iter_mut().for_each(|a| *a = (*a).max(x));
+            }
+            2 => {
+                tree.add(start..end, x / 10);
+                range.iter_mut().for_each(|a| *a += x / 10);
+            }
+            _ => {
+                tree.assign(start..end, x);
+                range.fill(x);
+            }
+        }
+
+        assert_eq!(
+            tree.max(start..end),
+            values[start..end].iter().max().copied()
+        );
+        assert_eq!(
+            tree.min(start..end),
+            values[start..end].iter().min().copied()
+        );
+        assert_eq!(tree.sum(start..end), values[start..end].iter().sum::<i64>());
+    }
+
+    assert_eq!(tree.to_vec(), values);
+}
+
+#[test]
+fn test_iterative_backend_matches_recursive() {
+    use handson2::iter_segtree::Iterative;
+    use handson2::problem1::SegmentTree;
+
+    for n in [1, 2, 7, 8, 9, 60] {
+        let mut rng = XorShift(3100 + n as u64);
+        let values: Vec<i64> = (0..n).map(|_| rng.range(-100, 100)).collect();
+        let mut recursive = SegmentTree::new(&values);
+        let mut iterative = SegmentTree::<i64, Iterative>::with_backend(&values);
+        let checkpoint = (recursive.checkpoint(), iterative.checkpoint());
+
+        for _ in 0..500 {
+            let range = rng.subrange(n);
+            let x = rng.range(-100, 100);
+            match rng.range(0, 3) {
+                0 => {
+                    recursive.chmin(range.clone(), x);
+                    iterative.chmin(range.clone(), x);
+                }
+                1 => {
+                    recursive.chmax(range.clone(), x);
+                    iterative.chmax(range.clone(), x);
+                }
+                2 => {
+                    recursive.add(range.clone(), x / 10);
+                    iterative.add(range.clone(), x / 10);
+                }
+                _ => {
+                    recursive.assign(range.clone(), x);
+                    iterative.assign(range.clone(), x);
+                }
+            }
+
+            let range = rng.subrange(n);
+            assert_eq!(recursive.sum(range.clone()), iterative.sum(range.clone()));
+            assert_eq!(recursive.min(range.clone()), iterative.min(range.clone()));
+            assert_eq!(
+                recursive.argmax(range.clone()),
+                iterative.argmax(range.clone())
+            );
+            assert_eq!(
+                recursive.first_at_least(range.start, x),
+                iterative.first_at_least(range.start, x)
+            );
+            assert_eq!(
+                recursive.last_at_least(range.end, x),
+                iterative.last_at_least(range.end, x)
+            );
+        }
+        assert_eq!(recursive.to_vec(), iterative.to_vec());
+
+        recursive.rollback(checkpoint.0);
+        iterative.rollback(checkpoint.1);
+        assert_eq!(iterative.to_vec(), values);
+        assert_eq!(recursive.to_vec(), values);
+    }
+}
+
+#[test]
+fn test_public_api_empty_ranges() {
+    let mut tree = handson2::problem1::SegmentTree::<i64>::new(&[]);
+    assert!(tree.is_empty());
+    assert_eq!(tree.max(0..0), None);
+    assert_eq!(tree.sum(0..0), 0);
+    assert!(tree.to_vec().is_empty());
+
+    let mut tree = handson2::problem1::SegmentTree::new(&[3, 1, 2]);
+    tree.chmin(1..1, 0);
+    assert_eq!(tree.min(2..2), None);
+    assert_eq!(tree.to_vec(), vec![3, 1, 2]);
+}
+
+#[test]
+#[should_panic(expected = "out of bounds")]
+fn test_public_api_out_of_bounds() {
+    let mut tree = handson2::problem1::SegmentTree::new(&[3, 1, 2]);
+    tree.max(1..4);
+}
+
+#[test]
+fn test_threshold_searches_against_brute_force() {
+    use handson2::problem1::SegmentTree;
+
+    let mut rng = XorShift(32);
+    let n = 40;
+    let mut values: Vec<i64> = (0..n).map(|_| rng.range(0, 100)).collect();
+    let mut tree = SegmentTree::new(&values);
+
+    for _ in 0..1000 {
+        // Pending chmin tags must be honoured by the descent.
+        let Range { start, end } = rng.subrange(n);
+        let t = rng.range(0, 100);
+        tree.chmin(start..end, t);
+        values[start..end].iter_mut().for_each(|a| *a = (*a).min(t));
+
+        let i = rng.range(0, n as i64) as usize;
+        let x = rng.range(0, 100);
+
+        let first = (i..n).find(|&k| values[k] >= x);
+        assert_eq!(tree.first_at_least(i, x), first);
+        assert_eq!(tree.max_right(i, |max| max < x), first.unwrap_or(n));
+
+        let last = (0..i).rev().find(|&k| values[k] >= x);
+        assert_eq!(tree.last_at_least(i, x), last);
+        assert_eq!(tree.min_left(i, |max| max < x), last.map_or(0, |k| k + 1));
+    }
+}
+
+#[test]
+fn test_argmax_and_max_count_against_brute_force() {
+    use handson2::problem1::{ArgMax, SegmentTree};
+
+    let mut rng = XorShift(33);
+    let n = 30;
+    // Few distinct values so that maximums are often tied.
+    let mut values: Vec<i64> = (0..n).map(|_| rng.range(0, 8)).collect();
+    let mut tree = SegmentTree::new(&values);
+
+    for _ in 0..1000 {
+        let Range { start, end } = rng.subrange(n);
+        let t = rng.range(0, 8);
+        tree.chmin(start..end, t);
+        values[start..end].iter_mut().for_each(|a| *a = (*a).min(t));
+
+        let Range { start, end } = rng.subrange(n);
+        let expected = values[start..end].iter().max().map(|&value| {
+            let positions: Vec<usize> = (start..end).filter(|&k| values[k] == value).collect();
+            ArgMax {
+                value,
+                first: positions[0],
+                last: *positions.last().unwrap(),
+                count: positions.len(),
+            }
+        });
+
+        assert_eq!(tree.argmax(start..end), expected);
+        assert_eq!(
+            tree.max_count(start..end),
+            expected.map_or(0, |argmax| argmax.count)
+        );
+    }
+}
+
+/// Random chmin / chmax / add / assign streams over a value pool containing
+/// the extremes of `$t`, checked against a plain vector.
+macro_rules! extreme_values_test {
+    ($name:ident, $t:ty, $seed:expr) => {
+        #[test]
+        fn $name() {
+            use handson2::problem1::SegmentTree;
+
+            let pool: [$t; 6] = [<$t>::MIN, <$t>::MIN + 1, 0, 1, <$t>::MAX - 1, <$t>::MAX];
+            let mut rng = XorShift($seed);
+            let n = 40;
+            let mut values: Vec<$t> = (0..n).map(|_| pool[rng.range(0, 5) as usize]).collect();
+            let mut tree: SegmentTree<$t> = values.iter().copied().collect();
+
+            for _ in 0..2000 {
+                let Range { start, end } = rng.subrange(n);
+                let x = pool[rng.range(0, 5) as usize];
+                let range = &mut values[start..end];
+
+                match rng.range(0, 3) {
+                    0 => {
+                        tree.chmin(start..end, x);
+                        range.iter_mut().for_each(|a| *a = (*a).min(x));
+                    }
+                    1 => {
+                        tree.chmax(start..end, x);
+                        range.iter_mut().for_each(|a| *a = (*a).max(x));
+                    }
+                    2 => {
+                        // Only additions that keep every value in range
+                        let delta = rng.range(0, 2) as $t;
+                        if range.iter().all(|a| a.checked_add(delta).is_some()) {
+                            tree.add(start..end, delta);
+                            range.iter_mut().for_each(|a| *a += delta);
+                        }
+                    }
+                    _ => {
+                        tree.assign(start..end, x);
+                        range.fill(x);
+                    }
+                }
+
+                let slice = &values[start..end];
+                assert_eq!(tree.max(start..end), slice.iter().max().copied());
+                assert_eq!(tree.min(start..end), slice.iter().min().copied());
+                assert_eq!(
+                    tree.sum(start..end),
+                    slice.iter().fold(0, |acc: $t, &a| acc.wrapping_add(a))
+                );
+                assert_eq!(
+                    tree.argmax(start..end).map(|argmax| argmax.value),
+                    slice.iter().max().copied()
+                );
+            }
+
+            assert_eq!(tree.to_vec(), values);
+        }
+    };
+}
+
+extreme_values_test!(test_extreme_values_i32, i32, 34);
+extreme_values_test!(test_extreme_values_i64, i64, 35);
+extreme_values_test!(test_extreme_values_u64, u64, 36);
+extreme_values_test!(test_extreme_values_i128, i128, 37);
+
+#[test]
+fn test_extremes_are_not_mistaken_for_missing_values() {
+    use handson2::problem1::SegmentTree;
+
+    // i64::MIN is a real second maximum here and must shift with the add
+    let mut tree = SegmentTree::new(&[i64::MIN, 5, i64::MIN, 5]);
+    tree.add(0..4, 3);
+    tree.chmin(0..4, 6);
+    assert_eq!(tree.to_vec(), vec![i64::MIN + 3, 6, i64::MIN + 3, 6]);
+    assert_eq!(tree.min(0..4), Some(i64::MIN + 3));
+
+    // Bounds shifted past the ends of the type must not overflow
+    let mut tree = SegmentTree::new(&[100, 200]);
+    tree.chmax(0..2, i64::MIN + 1);
+    tree.add(0..2, -5);
+    tree.chmin(0..2, i64::MAX - 1);
+    tree.add(0..2, 5);
+    assert_eq!(tree.to_vec(), vec![100, 200]);
+
+    // A clamped maximum shifted by a large add must not overflow
+    let values = [2_000_000_000, 0, 5, 10, 1, 2, 3, 4];
+    let mut tree = SegmentTree::<i32>::new(&values);
+    tree.chmin(0..8, 100);
+    tree.add(0..8, 200_000_000);
+    let expected: Vec<i32> = values.iter().map(|&v| v.min(100) + 200_000_000).collect();
+    assert_eq!(tree.max(0..4), Some(200_000_100));
+    assert_eq!(tree.min(0..8), Some(200_000_000));
+    assert_eq!(
+        tree.sum(0..8),
+        expected.iter().fold(0_i32, |a, &b| a.wrapping_add(b))
+    );
+    assert_eq!(tree.to_vec(), expected);
+
+    // Pending additions separated by a clamp may sum past the ends of the type
+    let mut tree = SegmentTree::new(&[0; 4]);
+    tree.add(0..4, i64::MAX);
+    tree.chmin(0..4, 0);
+    tree.add(0..4, 1);
+    assert_eq!(tree.to_vec(), vec![1; 4]);
+    assert_eq!(tree.sum(0..4), 4);
+
+    let mut tree = SegmentTree::new(&[i64::MIN, 0, i64::MIN, 0]);
+    tree.add(0..4, i64::MAX);
+    tree.chmin(0..4, 0);
+    tree.add(0..4, 1);
+    assert_eq!(tree.to_vec(), vec![0, 1, 0, 1]);
+    for _ in 0..200 {
+        tree.add(0..4, i64::MAX - 1);
+        tree.chmin(0..4, 1);
+    }
+    assert_eq!(tree.max(0..4), Some(1));
+
+    // Sums are exact whenever the result fits, even if partial sums overflow
+    let mut tree = SegmentTree::new(&[i64::MAX, i64::MAX, i64::MIN, i64::MIN]);
+    assert_eq!(tree.sum(0..4), -2);
+    let mut tree = SegmentTree::new(&[u64::MAX, u64::MAX]);
+    tree.assign(1..2, 0);
+    assert_eq!(tree.sum(0..2), u64::MAX);
+}
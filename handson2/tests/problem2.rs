@@ -1,25 +1,21 @@
-/// Normalize txt test file line endings and trailing spaces
-fn normalize(s: &str) -> String {
-    s.replace("\r\n", "\n").trim_end().to_string()
-}
-
-fn load_case(i: usize) -> (String, String) {
-    let input_path = format!("data/problem2/input{}.txt", i);
-    let output_path = format!("data/problem2/output{}.txt", i);
+mod common;
 
-    let input = std::fs::read_to_string(std::path::Path::new("tests").join(&input_path))
-        .unwrap_or_else(|_| panic!("Cannot read {}", input_path));
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::ops::Range;
 
-    let expected = std::fs::read_to_string(std::path::Path::new("tests").join(&output_path))
-        .unwrap_or_else(|_| panic!("Cannot read {}", output_path));
+use handson2::lazy_segtree::{Add, LazySegTree, MinMax, MinMaxValue};
+use handson2::problem2::{
+    Backend, Convention, InputError, parse_weighted_segments, solve_dynamic, solve_with,
+    try_solve_dynamic, try_solve_with,
+};
 
-    (input, expected)
-}
+use common::{XorShift, load_case, normalize};
 
 #[test]
 fn test_all_problem2_cases() {
     for i in 0..7 {
-        let (input, expected) = load_case(i);
+        let (input, expected) = load_case("problem2", i);
         let got = handson2::problem2::solve(&input);
 
         assert_eq!(
@@ -30,3 +26,734 @@ fn test_all_problem2_cases() {
         );
     }
 }
+
+/// Adds `offset` to every coordinate of a Problem 2 input.
+fn shift_input(input: &str, offset: u64) -> String {
+    let mut tokens = input.split_whitespace();
+    let n: usize = tokens.next().unwrap().parse().unwrap();
+    let m: usize = tokens.next().unwrap().parse().unwrap();
+    let mut next = || tokens.next().unwrap().parse::<u64>().unwrap();
+
+    let mut lines = vec![format!("{n} {m}")];
+    for _ in 0..n {
+        let (left, right) = (next(), next());
+        lines.push(format!("{} {}", left + offset, right + offset));
+    }
+    for _ in 0..m {
+        let (left, right, k) = (next(), next(), next());
+        lines.push(format!("{} {} {k}", left + offset, right + offset));
+    }
+    lines.join("\n")
+}
+
+#[test]
+fn test_huge_coordinates_match_dense_answers() {
+    for i in 0..7 {
+        let (input, expected) = load_case("problem2", i);
+        let max_coord = input
+            .split_whitespace()
+            .skip(2)
+            .map(|s| s.parse::<u64>().unwrap())
+            .max()
+            .unwrap();
+
+        for offset in [
+            1_000_000_000_000,
+            1_000_000_000_000_000_000,
+            u64::MAX - max_coord,
+        ] {
+            let shifted = shift_input(&input, offset);
+            for backend in [
+                Backend::Sparse,
+                Backend::Compressed,
+                Backend::PositionLists,
+                Backend::Mo,
+                Backend::SparseTable,
+                Backend::Iterative,
+            ] {
+                let got = solve_with(&shifted, backend);
+                assert_eq!(
+                    normalize(&got),
+                    normalize(&expected),
+                    "Mismatch in Problem 2 case {} shifted by {} with {:?}",
+                    i,
+                    offset,
+                    backend
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_all_backends_on_problem2_cases() {
+    for i in 0..7 {
+        let (input, expected) = load_case("problem2", i);
+        for backend in [
+            Backend::Dense,
+            Backend::Sparse,
+            Backend::Compressed,
+            Backend::PositionLists,
+            Backend::Mo,
+            Backend::SparseTable,
+            Backend::Iterative,
+        ] {
+            let got = solve_with(&input, backend);
+            assert_eq!(
+                normalize(&got),
+                normalize(&expected),
+                "Mismatch in Problem 2 case {} with {:?}",
+                i,
+                backend
+            );
+        }
+    }
+}
+
+#[test]
+fn test_random_inputs_match_dense_backend() {
+    for seed in 1..=50 {
+        let mut rng = XorShift(380 + seed);
+        let max_coord = rng.range(0, 60);
+        let n = rng.range(0, 15);
+        let m = rng.range(1, 30);
+
+        let mut lines = vec![format!("{n} {m}")];
+        for _ in 0..n + m {
+            let left = rng.range(0, max_coord);
+            let right = rng.range(left, max_coord);
+            lines.push(format!("{left} {right}"));
+        }
+        for line in &mut lines[1 + n as usize..] {
+            line.push_str(&format!(" {}", rng.range(0, 4)));
+        }
+        let input = lines.join("\n");
+
+        let expected = solve_with(&input, Backend::Dense);
+        for backend in [
+            Backend::Sparse,
+            Backend::Compressed,
+            Backend::PositionLists,
+            Backend::Mo,
+            Backend::SparseTable,
+            Backend::Iterative,
+        ] {
+            assert_eq!(
+                solve_with(&input, backend),
+                expected,
+                "seed {seed} with {backend:?}"
+            );
+        }
+    }
+}
+
+/// Coverage alternating between 0 and 2 over `0..2 * pairs`: every node of
+/// the min/max tree has `min = 0` and `max = 2`, so `k = 1` is never pruned.
+fn alternating_input(pairs: u64, queries: u64, seed: u64) -> String {
+    let mut rng = XorShift(seed);
+    let mut lines = vec![format!("{} {queries}", 2 * pairs)];
+    for i in 0..pairs {
+        lines.push(format!("{0} {0}", 2 * i + 1));
+        lines.push(format!("{0} {0}", 2 * i + 1));
+    }
+    for _ in 0..queries {
+        let left = rng.range(0, 2 * pairs - 1);
+        let right = rng.range(left, 2 * pairs - 1);
+        lines.push(format!("{left} {right} {}", rng.range(0, 2)));
+    }
+    lines.join("\n")
+}
+
+#[test]
+fn test_adversarial_alternating_coverage() {
+    let input = alternating_input(2_000, 500, 390);
+    let expected = solve_with(&input, Backend::Dense);
+    assert!(expected.lines().any(|line| line == "1"));
+    assert!(expected.lines().any(|line| line == "0"));
+
+    for backend in [
+        Backend::Compressed,
+        Backend::PositionLists,
+        Backend::Mo,
+        Backend::SparseTable,
+        Backend::Iterative,
+    ] {
+        assert_eq!(solve_with(&input, backend), expected, "{backend:?}");
+    }
+
+    // Count the work per query on the same coverage: the min/max search
+    // visits at least every position of a k = 1 range, while a sorted
+    // position list (what PositionLists keeps per value) needs one binary
+    // search. `cargo bench --bench problem2` shows the wall-clock gap.
+    let n = 4_000;
+    let coverage: Vec<i64> = (0..n).map(|x| if x % 2 == 1 { 2 } else { 0 }).collect();
+    let mut tree: LazySegTree<MinMax, Add> =
+        coverage.iter().copied().map(MinMaxValue::from).collect();
+    let mut positions: HashMap<i64, Vec<usize>> = HashMap::new();
+    for (x, &value) in coverage.iter().enumerate() {
+        positions.entry(value).or_default().push(x);
+    }
+
+    let mut rng = XorShift(391);
+    let (mut tree_visits, mut min_visits) = (0, 0);
+    let (mut search_steps, mut max_steps) = (0, 0);
+    for _ in 0..500 {
+        let Range { start, end } = rng.subrange(n);
+        let k = rng.range(0, 2);
+
+        let visits = Cell::new(0);
+        let found = tree.find_first(start..end, |node| {
+            visits.set(visits.get() + 1);
+            node.min <= k && k <= node.max
+        });
+        tree_visits += visits.get();
+        if k == 1 {
+            min_visits += end - start;
+        }
+
+        let steps = Cell::new(0);
+        let list = positions.get(&k).map_or(&[][..], Vec::as_slice);
+        let first = list.partition_point(|&x| {
+            steps.set(steps.get() + 1);
+            x < start
+        });
+        let witness = list.get(first).filter(|&&x| x < end).copied();
+        search_steps += steps.get();
+        max_steps += list.len().max(1).ilog2() as usize + 2;
+
+        assert_eq!(found, witness);
+    }
+    assert!(tree_visits >= min_visits, "{tree_visits} < {min_visits}");
+    assert!(search_steps <= max_steps, "{search_steps} > {max_steps}");
+    assert!(
+        tree_visits > 50 * search_steps,
+        "{tree_visits} visits vs {search_steps} steps"
+    );
+}
+
+#[test]
+fn test_weighted_inputs_against_brute_force() {
+    for seed in 1..=50 {
+        let mut rng = XorShift(450 + seed);
+        let max_coord = rng.range(0, 40);
+        let n = rng.range(0, 10);
+        let m = rng.range(1, 30);
+
+        // Mix weighted and unweighted lines; weights lie in -3..=3
+        let mut lines = vec![format!("{n} {m}")];
+        let mut segments = Vec::new();
+        for _ in 0..n {
+            let left = rng.range(0, max_coord);
+            let right = rng.range(left, max_coord);
+            let weight = rng.range(0, 7) as i64 - 3;
+            if weight == 1 && rng.range(0, 1) == 0 {
+                lines.push(format!("{left} {right}"));
+            } else {
+                lines.push(format!("{left} {right} {weight}"));
+            }
+            segments.push((left..=right, weight));
+        }
+
+        let mut expected = Vec::new();
+        for _ in 0..m {
+            let left = rng.range(0, max_coord + 5);
+            let right = rng.range(left, max_coord + 5);
+            let k = rng.range(0, 8) as i64 - 4;
+            lines.push(format!("{left} {right} {k}"));
+
+            let exists = (left..=right).any(|x| {
+                let coverage: i64 = segments
+                    .iter()
+                    .filter(|(seg, _)| seg.contains(&x))
+                    .map(|(_, weight)| weight)
+                    .sum();
+                coverage == k
+            });
+            expected.push(if exists { "1" } else { "0" });
+        }
+        let input = lines.join("\n");
+
+        assert_eq!(parse_weighted_segments(&input), segments);
+        for backend in [
+            Backend::Dense,
+            Backend::Sparse,
+            Backend::Compressed,
+            Backend::PositionLists,
+            Backend::Mo,
+            Backend::SparseTable,
+            Backend::Iterative,
+        ] {
+            assert_eq!(
+                solve_with(&input, backend),
+                expected.join("\n"),
+                "seed {seed} with {backend:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_extreme_weights() {
+    let backends = [
+        Backend::Dense,
+        Backend::Sparse,
+        Backend::Compressed,
+        Backend::PositionLists,
+        Backend::Mo,
+        Backend::SparseTable,
+        Backend::Iterative,
+    ];
+
+    // Neighbouring extremes: the difference at 1 is MAX - MIN, but every
+    // coverage fits
+    let input = format!(
+        "2 3\n0 0 {}\n1 1 {}\n0 1 {}\n0 1 {}\n0 2 0",
+        i64::MIN,
+        i64::MAX,
+        i64::MIN,
+        i64::MAX
+    );
+    for backend in backends {
+        assert_eq!(solve_with(&input, backend), "1\n1\n1", "{backend:?}");
+    }
+
+    // Overlapping MAX weights add up beyond i64
+    let input = format!("2 1\n-5 5 {0}\n3 9 {0}\n0 9 0", i64::MAX);
+    let error = InputError::CoverageOverflow { position: 3 };
+    for backend in backends {
+        assert_eq!(
+            try_solve_with(&input, backend, Convention::Closed),
+            Err(error.clone()),
+            "{backend:?}"
+        );
+    }
+    assert_eq!(
+        error.to_string(),
+        "the weights covering position 3 add up beyond i64"
+    );
+
+    // The total of MIN, MAX and 1 fits, but the sparse tree may add MAX and
+    // 1 first, so it is rejected as well
+    let input = format!("3 1\n0 9 {}\n5 5 {}\n5 5 1\n0 9 0", i64::MIN, i64::MAX);
+    for backend in backends {
+        assert_eq!(
+            try_solve_with(&input, backend, Convention::Closed),
+            Err(InputError::CoverageOverflow { position: 5 }),
+            "{backend:?}"
+        );
+    }
+}
+
+#[test]
+fn test_signed_coordinates_and_conventions_against_brute_force() {
+    for seed in 1..=50 {
+        let mut rng = XorShift(470 + seed);
+        let coord = |rng: &mut XorShift| rng.range(0, 40) as i64 - 20;
+        let n = rng.range(0, 10);
+        let m = rng.range(1, 30);
+
+        let mut lines = vec![format!("{n} {m}")];
+        let mut segments = Vec::new();
+        for _ in 0..n {
+            let (a, b) = (coord(&mut rng), coord(&mut rng));
+            lines.push(format!("{} {}", a.min(b), a.max(b)));
+            segments.push((a.min(b), a.max(b)));
+        }
+        let mut queries = Vec::new();
+        for _ in 0..m {
+            let (a, b) = (coord(&mut rng), coord(&mut rng));
+            let k = rng.range(0, 3) as i64;
+            lines.push(format!("{} {} {k}", a.min(b), a.max(b)));
+            queries.push((a.min(b), a.max(b), k));
+        }
+        let input = lines.join("\n");
+
+        for convention in [Convention::Closed, Convention::HalfOpen] {
+            let positions = |left: i64, right: i64| match convention {
+                Convention::Closed => left..right + 1,
+                Convention::HalfOpen => left..right,
+            };
+            let expected: Vec<&str> = queries
+                .iter()
+                .map(|&(left, right, k)| {
+                    let exists = positions(left, right).any(|x| {
+                        let coverage = segments
+                            .iter()
+                            .filter(|&&(l, r)| positions(l, r).contains(&x))
+                            .count();
+                        coverage as i64 == k
+                    });
+                    if exists { "1" } else { "0" }
+                })
+                .collect();
+
+            for backend in [
+                Backend::Dense,
+                Backend::Sparse,
+                Backend::Compressed,
+                Backend::PositionLists,
+                Backend::Mo,
+                Backend::SparseTable,
+                Backend::Iterative,
+            ] {
+                assert_eq!(
+                    try_solve_with(&input, backend, convention),
+                    Ok(expected.join("\n")),
+                    "seed {seed} with {backend:?} and {convention:?}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_coordinate_span_limits() {
+    // -1..=u64::MAX - 1 spans exactly 2^64 positions
+    let input = format!("2 2\n-1 -1\n{0} {0}\n-1 {0} 1\n0 5 0", u64::MAX - 1);
+    for backend in [
+        Backend::Sparse,
+        Backend::Compressed,
+        Backend::PositionLists,
+        Backend::Mo,
+        Backend::SparseTable,
+        Backend::Iterative,
+    ] {
+        assert_eq!(
+            try_solve_with(&input, backend, Convention::Closed),
+            Ok("1\n1".to_string())
+        );
+    }
+
+    let input = format!("1 0\n{} {}", i64::MIN, u64::MAX);
+    assert_eq!(
+        try_solve_with(&input, Backend::default(), Convention::Closed),
+        Err(InputError::SpanTooLarge {
+            min: i64::MIN.into(),
+            max: u64::MAX.into(),
+        })
+    );
+}
+
+#[test]
+fn test_invalid_inputs_are_reported() {
+    let cases = [
+        ("2 1\n1 5\n", InputError::UnexpectedEof),
+        ("1 1\n1 5\n0 3", InputError::UnexpectedEof),
+        (
+            "1 1\n1 five\n0 3 1",
+            InputError::InvalidNumber {
+                line: 2,
+                token: "five".to_string(),
+            },
+        ),
+        (
+            "2 1\n1 5\n6 -2\n0 3 1",
+            InputError::InvalidSegment {
+                index: 1,
+                left: 6,
+                right: -2,
+            },
+        ),
+        (
+            "1 1\n1 5\n4 3 1",
+            InputError::InvalidQuery {
+                index: 0,
+                left: 4,
+                right: 3,
+            },
+        ),
+    ];
+    for (input, error) in cases {
+        for convention in [Convention::Closed, Convention::HalfOpen] {
+            assert_eq!(
+                try_solve_with(input, Backend::default(), convention),
+                Err(error.clone()),
+                "{input:?}"
+            );
+        }
+    }
+    assert_eq!(
+        InputError::InvalidSegment {
+            index: 1,
+            left: 6,
+            right: -2
+        }
+        .to_string(),
+        "segment 1 has L = 6 > R = -2"
+    );
+}
+
+#[test]
+fn test_trailing_tokens_are_reported() {
+    let trailing = |line: usize, token: &str| InputError::TrailingToken {
+        line,
+        token: token.to_string(),
+    };
+    let cases = [
+        (
+            "1 1 7
+1 5
+0 3 1",
+            trailing(1, "7"),
+        ),
+        (
+            "1 1
+1 2 3 4
+0 3 1",
+            trailing(2, "4"),
+        ),
+        (
+            "1 1
+1 5
+1 2 3 4",
+            trailing(3, "4"),
+        ),
+        // Several records on one line are no longer accepted
+        (
+            "2 1
+1 5 2 6
+0 3 1",
+            trailing(2, "6"),
+        ),
+    ];
+    for (input, error) in cases {
+        assert_eq!(
+            try_solve_with(input, Backend::default(), Convention::Closed),
+            Err(error),
+            "{input:?}"
+        );
+    }
+    assert_eq!(
+        trailing(3, "4").to_string(),
+        "line 3: unexpected trailing token \"4\""
+    );
+}
+
+#[test]
+fn test_half_open_empty_ranges() {
+    // [3, 3) is empty: the segment covers nothing and the query has no position
+    let input = "2 3\n3 3\n0 10\n3 3 0\n3 4 2\n3 4 1";
+    assert_eq!(
+        try_solve_with(input, Backend::default(), Convention::HalfOpen),
+        Ok("0\n0\n1".to_string())
+    );
+    assert_eq!(
+        try_solve_with(input, Backend::default(), Convention::Closed),
+        Ok("0\n1\n1".to_string())
+    );
+}
+
+#[test]
+#[should_panic(expected = "Invalid IsThere input: query 0 has i = 4 > j = 3")]
+fn test_solve_panics_on_invalid_input() {
+    handson2::problem2::solve("1 1\n1 5\n4 3 1");
+}
+
+#[test]
+fn test_all_dynamic_cases() {
+    for i in 0..6 {
+        let input_path = format!("tests/data/problem2_dynamic/input{}.txt", i);
+        let output_path = format!("tests/data/problem2_dynamic/output{}.txt", i);
+        let input = std::fs::read_to_string(&input_path)
+            .unwrap_or_else(|_| panic!("Cannot read {}", input_path));
+        let expected = std::fs::read_to_string(&output_path)
+            .unwrap_or_else(|_| panic!("Cannot read {}", output_path));
+
+        assert_eq!(
+            normalize(&solve_dynamic(&input)),
+            normalize(&expected),
+            "Mismatch in dynamic case {}",
+            i
+        );
+    }
+}
+
+#[test]
+fn test_dynamic_stream_matches_static_solver() {
+    // Adding every segment first and then querying is the static problem.
+    for i in 0..7 {
+        let (input, expected) = load_case("problem2", i);
+        let mut tokens = input.split_whitespace();
+        let n: usize = tokens.next().unwrap().parse().unwrap();
+        let m: usize = tokens.next().unwrap().parse().unwrap();
+        let tokens: Vec<&str> = tokens.collect();
+
+        let mut lines = vec![(n + m).to_string()];
+        lines.extend(
+            tokens[..2 * n]
+                .chunks(2)
+                .map(|s| format!("add {}", s.join(" "))),
+        );
+        lines.extend(
+            tokens[2 * n..]
+                .chunks(3)
+                .map(|q| format!("isthere {}", q.join(" "))),
+        );
+
+        assert_eq!(
+            normalize(&solve_dynamic(&lines.join("\n"))),
+            normalize(&expected),
+            "Mismatch in Problem 2 case {} as a stream",
+            i
+        );
+    }
+}
+
+#[test]
+#[should_panic(expected = "was not added")]
+fn test_dynamic_remove_unknown_segment_panics() {
+    solve_dynamic("2\nadd 1 3\nremove 1 4");
+}
+
+#[test]
+#[should_panic(expected = "operation 1 (add 5 3) has L = 5 > R = 3")]
+fn test_dynamic_reversed_segment_panics() {
+    solve_dynamic("2\nadd 1 3\nadd 5 3");
+}
+
+#[test]
+#[should_panic(expected = "operation 1 (isthere 4 2) has i = 4 > j = 2")]
+fn test_dynamic_reversed_query_panics() {
+    solve_dynamic("2\nadd 1 3\nisthere 4 2 0");
+}
+
+#[test]
+fn test_dynamic_signed_coordinates_and_conventions() {
+    let input = "5\nadd -10 -1\nadd -5 3\nisthere -20 -6 1\nisthere -3 -1 2\nisthere 0 3 2";
+    assert_eq!(
+        try_solve_dynamic(input, Convention::Closed),
+        Ok("1\n1\n0".to_string())
+    );
+
+    // `[-5, 3)` stops before 3, and the empty `[2, 2)` can still be removed
+    let input = "6\nadd -5 3\nisthere 3 9 1\nisthere -9 -4 1\nadd 2 2\nremove 2 2\nisthere 2 2 0";
+    assert_eq!(
+        try_solve_dynamic(input, Convention::HalfOpen),
+        Ok("0\n1\n0".to_string())
+    );
+
+    // Adding every segment of a signed static input replays it
+    let segments = [(-7, -2, 1), (-4, 6, 1), (i64::MIN, -3, 1)];
+    let queries = [(-9, -8, 1), (-4, -3, 3), (5, 9, 0), (-2, 7, 2)];
+    let mut input = format!("{} {}\n", segments.len(), queries.len());
+    let mut stream = format!("{}\n", segments.len() + queries.len());
+    for (left, right, _) in segments {
+        input += &format!("{left} {right}\n");
+        stream += &format!("add {left} {right}\n");
+    }
+    for (left, right, k) in queries {
+        input += &format!("{left} {right} {k}\n");
+        stream += &format!("isthere {left} {right} {k}\n");
+    }
+    for convention in [Convention::Closed, Convention::HalfOpen] {
+        assert_eq!(
+            try_solve_dynamic(&stream, convention),
+            try_solve_with(&input, Backend::default(), convention)
+        );
+    }
+}
+
+#[test]
+fn test_dynamic_random_streams_against_brute_force() {
+    for seed in 1..=50 {
+        let mut rng = XorShift(520 + seed);
+        let q = rng.range(1, 60);
+
+        let mut lines = vec![q.to_string()];
+        let mut active: Vec<(i64, i64)> = Vec::new();
+        let mut expected = Vec::new();
+        for _ in 0..q {
+            let left = rng.range(0, 30) as i64 - 10;
+            // Half of the ranges are a single position
+            let right = if rng.range(0, 1) == 0 {
+                left
+            } else {
+                left + rng.range(0, 10) as i64
+            };
+            match rng.range(0, 2) {
+                0 => {
+                    lines.push(format!("add {left} {right}"));
+                    active.push((left, right));
+                }
+                1 if !active.is_empty() => {
+                    let (left, right) = active.swap_remove(rng.below(active.len()));
+                    lines.push(format!("remove {left} {right}"));
+                }
+                _ => {
+                    let k = rng.range(0, 3) as i64;
+                    lines.push(format!("isthere {left} {right} {k}"));
+                    let exists = (left..=right).any(|x| {
+                        let coverage = active.iter().filter(|&&(l, r)| l <= x && x <= r).count();
+                        coverage as i64 == k
+                    });
+                    expected.push(if exists { "1" } else { "0" });
+                }
+            }
+        }
+
+        assert_eq!(
+            solve_dynamic(&lines.join("\n")),
+            expected.join("\n"),
+            "seed {seed}"
+        );
+    }
+}
+
+#[test]
+fn test_invalid_dynamic_inputs_are_reported() {
+    let cases = [
+        ("2\nadd -1 3", InputError::UnexpectedEof),
+        (
+            "1\nadd -1 x",
+            InputError::InvalidNumber {
+                line: 2,
+                token: "x".to_string(),
+            },
+        ),
+        (
+            "1\nadd 1 3 1",
+            InputError::TrailingToken {
+                line: 2,
+                token: "1".to_string(),
+            },
+        ),
+        (
+            "2\nadd 1 3\nmove 1 3",
+            InputError::UnknownOperation {
+                line: 3,
+                name: "move".to_string(),
+            },
+        ),
+        (
+            "2\nadd 1 3\nisthere -2 -4 0",
+            InputError::InvalidOperation {
+                index: 1,
+                name: "isthere".to_string(),
+                left: -2,
+                right: -4,
+            },
+        ),
+        (
+            "2\nadd -1 3\nremove -1 4",
+            InputError::NotAdded {
+                index: 1,
+                left: -1,
+                right: 4,
+            },
+        ),
+        (
+            "2\nadd -1 0\nadd 0 18446744073709551615",
+            InputError::SpanTooLarge {
+                min: -1,
+                max: u64::MAX.into(),
+            },
+        ),
+    ];
+    for (input, error) in cases {
+        assert_eq!(
+            try_solve_dynamic(input, Convention::Closed),
+            Err(error),
+            "{input:?}"
+        );
+    }
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use handson2::problem2_2d::{Backend, solve_with};
+
+use common::XorShift;
+
+type Rect = (u64, u64, u64, u64);
+
+fn random_rect(rng: &mut XorShift, max_x: u64, max_y: u64) -> Rect {
+    let (x1, y1) = (rng.range(0, max_x), rng.range(0, max_y));
+    (x1, y1, rng.range(x1, max_x), rng.range(y1, max_y))
+}
+
+fn brute_force(rects: &[Rect], queries: &[(Rect, i64)]) -> String {
+    queries
+        .iter()
+        .map(|&((x1, y1, x2, y2), k)| {
+            let exists = (x1..=x2).any(|x| {
+                (y1..=y2).any(|y| {
+                    let coverage = rects
+                        .iter()
+                        .filter(|r| r.0 <= x && x <= r.2 && r.1 <= y && y <= r.3)
+                        .count();
+                    coverage as i64 == k
+                })
+            });
+            if exists { "1" } else { "0" }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn format_input(rects: &[Rect], queries: &[(Rect, i64)]) -> String {
+    let mut lines = vec![format!("{} {}", rects.len(), queries.len())];
+    for (x1, y1, x2, y2) in rects {
+        lines.push(format!("{x1} {y1} {x2} {y2}"));
+    }
+    for ((x1, y1, x2, y2), k) in queries {
+        lines.push(format!("{x1} {y1} {x2} {y2} {k}"));
+    }
+    lines.join("\n")
+}
+
+#[test]
+fn test_random_grids_against_brute_force() {
+    for seed in 1..=50 {
+        let mut rng = XorShift(600 + seed);
+        let (max_x, max_y) = (rng.range(0, 15), rng.range(0, 15));
+        let rects: Vec<Rect> = (0..rng.range(0, 8))
+            .map(|_| random_rect(&mut rng, max_x, max_y))
+            .collect();
+        let queries: Vec<(Rect, i64)> = (0..rng.range(1, 30))
+            .map(|_| {
+                let rect = random_rect(&mut rng, max_x + 3, max_y + 3);
+                (rect, rng.range(0, 4) as i64)
+            })
+            .collect();
+
+        let input = format_input(&rects, &queries);
+        let expected = brute_force(&rects, &queries);
+        for backend in [Backend::Grid, Backend::Sweep] {
+            assert_eq!(
+                solve_with(&input, backend),
+                expected,
+                "seed {seed} with {backend:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_single_row_matches_problem2() {
+    // Segments [L, R] become rectangles [L, R] × [0, 0]
+    for i in 0..7 {
+        let input = std::fs::read_to_string(format!("tests/data/problem2/input{i}.txt"))
+            .unwrap_or_else(|_| panic!("Cannot read input {i}"));
+        let mut lines = input.lines().filter(|line| !line.trim().is_empty());
+        let mut rows = vec![lines.next().unwrap().to_string()];
+        for line in lines {
+            let values: Vec<&str> = line.split_whitespace().collect();
+            let mut row = format!("{} 0 {} 0", values[0], values[1]);
+            if let Some(k) = values.get(2) {
+                row.push_str(&format!(" {k}"));
+            }
+            rows.push(row);
+        }
+        let input_2d = rows.join("\n");
+
+        let expected = handson2::problem2::solve(&input);
+        for backend in [Backend::Grid, Backend::Sweep] {
+            assert_eq!(
+                solve_with(&input_2d, backend),
+                expected,
+                "case {i} with {backend:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_sweep_on_huge_coordinates() {
+    let big = u64::MAX - 10;
+    let rects = [
+        (0, 0, u64::MAX, u64::MAX),
+        (big, big, u64::MAX, u64::MAX),
+        (1 << 40, 0, 1 << 41, 5),
+    ];
+    let queries = [
+        ((0, 0, 10, 10), 1),
+        ((0, 0, u64::MAX, u64::MAX), 2),
+        ((big - 1, big - 1, big, big), 2),
+        ((big - 1, big - 1, big - 1, u64::MAX), 2),
+        ((1 << 40, 6, 1 << 41, 100), 2),
+        ((1 << 40, 5, 1 << 40, 5), 2),
+        ((u64::MAX, u64::MAX, u64::MAX, u64::MAX), 0),
+    ];
+    assert_eq!(
+        solve_with(&format_input(&rects, &queries), Backend::Sweep),
+        "1\n1\n1\n0\n0\n1\n0"
+    );
+}
+
+#[test]
+#[should_panic(expected = "is empty")]
+fn test_empty_rectangle_panics() {
+    solve_with("1 0\n3 0 2 5", Backend::Sweep);
+}
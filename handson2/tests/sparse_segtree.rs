@@ -0,0 +1,66 @@
+mod common;
+
+use std::ops::Range;
+
+use handson2::sparse_segtree::SparseSegTree;
+
+use common::XorShift;
+
+#[test]
+fn test_sparse_against_brute_force() {
+    // A small window placed at both ends of the coordinate space.
+    for base in [0, 1 << 40, u64::MAX - 63] {
+        for seed in 1..=10 {
+            let mut rng = XorShift(370 + seed);
+            let mut values = [0_i64; 64];
+            let mut tree = SparseSegTree::new();
+
+            for _ in 0..300 {
+                let Range { start, end } = rng.subrange(63);
+                let coords = base + start as u64..=base + end as u64;
+
+                if rng.range(0, 1) == 0 {
+                    let add = rng.range(-5, 5);
+                    tree.add(coords, add);
+                    values[start..=end].iter_mut().for_each(|v| *v += add);
+                } else {
+                    let window = &values[start..=end];
+                    assert_eq!(tree.min(coords.clone()), window.iter().min().copied());
+                    assert_eq!(tree.max(coords.clone()), window.iter().max().copied());
+
+                    let k = rng.range(-5, 5);
+                    let expected = window.iter().position(|&v| v == k);
+                    let got = tree.find_first(coords, |v| v.min <= k && k <= v.max);
+                    assert_eq!(got, expected.map(|p| base + (start + p) as u64));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_untouched_coordinates_are_zero() {
+    let mut tree = SparseSegTree::new();
+    assert_eq!(tree.max(0..=u64::MAX), Some(0));
+
+    tree.add(5..=u64::MAX, 3);
+    assert_eq!(tree.min(0..=u64::MAX), Some(0));
+    assert_eq!(tree.min(5..=u64::MAX), Some(3));
+    assert_eq!(tree.find_first(0..=u64::MAX, |v| v.max >= 3), Some(5));
+    assert_eq!(tree.find_first(6..=u64::MAX, |v| v.min <= 0), None);
+
+    // Empty ranges (start > end).
+    let (start, end) = (7, 6);
+    assert_eq!(tree.max(start..=end), None);
+    assert_eq!(tree.find_first(start..=end, |_| true), None);
+}
+
+#[test]
+fn test_memory_grows_with_updates_only() {
+    let mut tree = SparseSegTree::new();
+    for i in 0..1_000_u64 {
+        tree.add(i * 1_000_000_007..=u64::MAX - i, 1);
+    }
+    // Two root-to-leaf paths per update.
+    assert!(tree.node_count() <= 1 + 1_000 * 2 * 64);
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use std::ops::Range;
+
+use handson2::lazy_segtree::{Max, Min, MinMax, MinMaxValue};
+use handson2::sparse_table::{Gcd, SparseTable};
+
+use common::XorShift;
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+#[test]
+fn test_queries_against_brute_force() {
+    for seed in 1..=30 {
+        let mut rng = XorShift(900 + seed);
+        let n = rng.range(0, 70) as usize;
+        let values: Vec<i64> = (0..n).map(|_| rng.range(0, 200) as i64 - 100).collect();
+        let divisible: Vec<u64> = (0..n).map(|_| 6 * rng.range(1, 20)).collect();
+
+        let min: SparseTable<Min> = values.iter().copied().collect();
+        let max: SparseTable<Max> = values.iter().copied().collect();
+        let min_max: SparseTable<MinMax> = values.iter().copied().map(MinMaxValue::from).collect();
+        let gcds: SparseTable<Gcd> = divisible.iter().copied().collect();
+        assert_eq!(min.len(), n);
+
+        for start in 0..=n {
+            for end in start..=n {
+                let window = &values[start..end];
+                let expected_min = window.iter().copied().min().unwrap_or(i64::MAX);
+                let expected_max = window.iter().copied().max().unwrap_or(i64::MIN);
+                assert_eq!(min.query(start..end), expected_min);
+                assert_eq!(max.query(start..end), expected_max);
+                assert_eq!(
+                    min_max.query(start..end),
+                    MinMaxValue {
+                        min: expected_min,
+                        max: expected_max,
+                    }
+                );
+                let expected_gcd = divisible[start..end].iter().fold(0, |a, &b| gcd(a, b));
+                assert_eq!(gcds.query(start..end), expected_gcd);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_find_first_against_brute_force() {
+    for seed in 1..=30 {
+        let mut rng = XorShift(930 + seed);
+        let n = rng.range(0, 50) as usize;
+        let values: Vec<i64> = (0..n).map(|_| rng.range(0, 6) as i64).collect();
+        let table: SparseTable<MinMax> = values.iter().copied().map(MinMaxValue::from).collect();
+
+        for _ in 0..100 {
+            let Range { start, end } = rng.subrange(n);
+            let k = rng.range(0, 7) as i64;
+            let expected = (start..end).find(|&i| values[i] == k);
+            assert_eq!(
+                table.find_first(start..end, |node| node.min <= k && k <= node.max),
+                expected
+            );
+        }
+    }
+}
+
+#[test]
+fn test_extreme_values() {
+    let values = [i64::MIN, i64::MAX, 0, i64::MIN];
+    let min: SparseTable<Min> = values.into_iter().collect();
+    let max: SparseTable<Max> = values.into_iter().collect();
+    assert_eq!(min.query(1..3), 0);
+    assert_eq!(max.query(0..4), i64::MAX);
+    assert_eq!(min.query(0..4), i64::MIN);
+
+    let gcds: SparseTable<Gcd> = [u64::MAX, 0, u64::MAX / 3].into_iter().collect();
+    assert_eq!(gcds.query(0..2), u64::MAX);
+    assert_eq!(gcds.query(0..3), u64::MAX / 3);
+}
+
+#[test]
+#[should_panic(expected = "Range 2..6 is out of bounds for length 5")]
+fn test_out_of_bounds_range_panics() {
+    let table: SparseTable<Min> = [1, 2, 3, 4, 5].into_iter().collect();
+    table.query(2..6);
+}